@@ -3,7 +3,13 @@ use crate::atdf;
 use crate::chip;
 use std::collections::BTreeMap;
 
-pub fn parse(el: &xmltree::Element) -> crate::Result<chip::Chip> {
+/// Parse the only device of an ATDF file
+///
+/// Errors if the file contains more than one device definition, use [`parse_all`] for those.
+pub fn parse(
+    el: &xmltree::Element,
+    interrupt_collisions: atdf::interrupt::CollisionPolicy,
+) -> crate::Result<chip::Chip> {
     let devices = el.first_child("devices")?;
     if devices.children.len() != 1 {
         return Err(
@@ -11,7 +17,87 @@ pub fn parse(el: &xmltree::Element) -> crate::Result<chip::Chip> {
         );
     }
 
-    let device = devices.first_child("device")?;
+    parse_device(el, devices.first_child("device")?, interrupt_collisions)
+}
+
+/// Parse all devices of an ATDF file
+pub fn parse_all(
+    el: &xmltree::Element,
+    interrupt_collisions: atdf::interrupt::CollisionPolicy,
+) -> crate::Result<Vec<chip::Chip>> {
+    el.first_child("devices")?
+        .iter_children_with_name("device", Some("devices"))
+        .map(|device| parse_device(el, device, interrupt_collisions))
+        .collect()
+}
+
+/// Parse one `<device>` of the ATDF file whose root element is `el`
+pub fn parse_device(
+    el: &xmltree::Element,
+    device: &xmltree::Element,
+    interrupt_collisions: atdf::interrupt::CollisionPolicy,
+) -> crate::Result<chip::Chip> {
+    debug_assert!(device.name == "device");
+
+    let properties = match device.get_child("property-groups") {
+        Some(el) => atdf::property::parse_groups(el)?,
+        None => BTreeMap::new(),
+    };
+    let signature = atdf::property::signature(&properties)?;
+
+    // Variants and pinouts are listed outside of the device definitions.  When the file defines
+    // more than one device, only keep the variants whose order code starts with the device name
+    // and the pinouts they reference.
+    let name = device.attr("name")?.clone();
+    let shared = el
+        .first_child("devices")?
+        .iter_children_with_name("device", Some("devices"))
+        .count()
+        > 1;
+
+    let variants: Vec<chip::Variant> = match el.get_child("variants") {
+        Some(variants) => variants
+            .iter_children_with_name("variant", Some("variants"))
+            .map(atdf::variant::parse)
+            .filter(|v| match v {
+                Ok(v) if shared => v
+                    .order_code
+                    .to_lowercase()
+                    .starts_with(&name.to_lowercase()),
+                _ => true,
+            })
+            .collect::<Result<_, _>>()?,
+        None => vec![],
+    };
+
+    let interfaces = match device.get_child("interfaces") {
+        Some(el) => atdf::property::parse_interfaces(el)?,
+        None => vec![],
+    };
+    let parameters = match device.get_child("parameters") {
+        Some(el) => atdf::property::parse_parameters(el)?,
+        None => BTreeMap::new(),
+    };
+
+    let pinouts = match el.get_child("pinouts") {
+        Some(pinouts) => pinouts
+            .iter_children_with_name("pinout", Some("pinouts"))
+            .map(atdf::pinout::parse)
+            .filter(|p| match p {
+                Ok(p) if shared => variants.iter().any(|v| v.pinout.as_ref() == Some(&p.name)),
+                _ => true,
+            })
+            .map(|r| r.map(|p| (p.name.clone(), p)))
+            .collect::<Result<_, _>>()?,
+        None => BTreeMap::new(),
+    };
+
+    let address_spaces = device
+        .first_child("address-spaces")?
+        .iter_children_with_name("address-space", Some("address-spaces"))
+        .map(atdf::memory::parse)
+        .map(|r| r.map(|a| (a.name.clone(), a)))
+        .collect::<Result<_, _>>()?;
 
     let peripherals = atdf::peripheral::parse_list(
         device.first_child("peripherals")?,
@@ -21,50 +107,45 @@ pub fn parse(el: &xmltree::Element) -> crate::Result<chip::Chip> {
     .map(|p| (p.name.clone(), p))
     .collect();
 
-    let interrupts_vec = device
-        .first_child("interrupts")?
-        .iter_children_with_name("interrupt", Some("interrupts"))
-        .map(atdf::interrupt::parse)
-        .collect::<Result<Vec<_>, _>>()?;
-
-    // Check for duplicate index, merge names if duplicate index exists
-    let mut interrupts = BTreeMap::<usize, chip::Interrupt>::new();
-    for int in interrupts_vec {
-        if let Some(existing_int) = interrupts.get_mut(&int.index) {
-            let old_name = existing_int.name.clone();
-            if let Some(split_idx) = int.name.find('_') {
-                existing_int.name.push_str(int.name.split_at(split_idx).1);
-            } else {
-                existing_int.name.push('_');
-                existing_int.name.push_str(&int.name);
-            }
-            log::warn!(
-                "Merging interrupt {} and {} to {}",
-                old_name,
-                int.name,
-                existing_int.name
-            );
-        } else {
-            interrupts.insert(int.index, int);
-        }
-    }
+    let interrupts =
+        atdf::interrupt::parse_list(device.first_child("interrupts")?, interrupt_collisions)?;
 
-    // Map interrupts from <usize, chip::Interrupt> to <std::string::String, chip::Interrupt>
-    let interrupts = interrupts
-        .values()
-        .map(|int| (int.name.clone(), int.clone()))
-        .collect();
+    let architecture = device.attr("architecture")?.clone();
+    let family = device.attr("family")?.clone();
+    let series = device.attr("series").ok().cloned();
+
+    // ATDF files do not carry a version of their own, the schema version is the best we have
+    // until a pack version is found.
+    let version = el.attributes.get("schema-version").cloned();
+    let description = Some(match (&series, &version) {
+        (Some(series), Some(version)) => format!(
+            "{name} ({family}, {series} series). Converted from ATDF schema version {version}."
+        ),
+        (None, Some(version)) => {
+            format!("{name} ({family}). Converted from ATDF schema version {version}.")
+        }
+        (Some(series), None) => format!("{name} ({family}, {series} series)."),
+        (None, None) => format!("{name} ({family})."),
+    });
 
     Ok(chip::Chip {
-        name: device.attr("name")?.clone(),
-        architecture: device.attr("architecture")?.clone(),
-        family: device.attr("family")?.clone(),
-        series: device.attr("series").ok().cloned(),
+        name,
+        architecture,
+        family,
+        series,
 
-        description: None,
+        description,
         vendor: None,
-        version: None,
+        version,
+
+        signature,
+        properties,
+        variants,
+        interfaces,
+        parameters,
+        pinouts,
 
+        address_spaces,
         peripherals,
         interrupts,
     })
@@ -3,17 +3,18 @@ use crate::atdf;
 use crate::chip;
 use crate::util;
 
+/// Parse a bitfield
+///
+/// Bitfields whose mask has holes (e.g. `0b00100111`) result in one field per contiguous run of
+/// bits, named `<NAME>_<n>` starting from the least significant one.  `register_size` is the
+/// size of the surrounding register in bytes.
 pub fn parse(
     bitfield_el: &xmltree::Element,
+    register_size: usize,
     value_groups: &atdf::values::ValueGroups,
-    mode_name: Option<&str>,
-) -> crate::Result<chip::Field> {
+) -> crate::Result<Vec<chip::Field>> {
     debug_assert!(bitfield_el.name == "bitfield");
-    let name: String = if let Some(mode) = mode_name {
-        format!("{}_{}", mode, bitfield_el.attr("name")?)
-    } else {
-        bitfield_el.attr("name")?.clone()
-    };
+    let name = bitfield_el.attr("name")?.clone();
     let description = bitfield_el
         .attributes
         .get("caption")
@@ -21,13 +22,25 @@ pub fn parse(
         .cloned();
     let values = bitfield_el.attributes.get("values");
 
-    // The range is defined by a mask.
-    // Not that in some cases there are bits withing this range, that do not belong to this mask
-    // (e.g. 0b00010010). Then the value restriction is unsafe.
     let mask = bitfield_el.attr("mask")?;
-    let (range, unsafe_range) = util::parse_mask(mask)?.ok_or_else(|| {
-        atdf::error::UnsupportedError::new(format!("mask {:?}", mask), bitfield_el)
-    })?;
+    let runs = util::parse_mask(mask)?;
+    if runs.is_empty() {
+        return Err(
+            atdf::error::UnsupportedError::new(format!("mask {:?}", mask), bitfield_el).into(),
+        );
+    }
+    let width: usize = runs.iter().map(|(lsb, msb)| msb - lsb + 1).sum();
+
+    // A `count` repeats the bitfield in the bits directly above it
+    let dim = match bitfield_el.attributes.get("count") {
+        Some(count) => Some(util::parse_int(count)?)
+            .filter(|count| *count > 1)
+            .map(|count| chip::Dim {
+                count,
+                increment: width,
+            }),
+        None => None,
+    };
 
     let restriction = if let Some(id) = values {
         let values = value_groups.get(id).ok_or_else(|| {
@@ -36,11 +49,10 @@ pub fn parse(
                 bitfield_el,
             )
         })?;
-        let mask_as_int = util::parse_int(mask)?;
-        let mask_as_int = mask_as_int >> mask_as_int.trailing_zeros();
+        let value_mask = (1 << width) - 1;
         let filtered_values: std::collections::BTreeMap<_, _> = values
             .iter()
-            .filter(|(_, ev)| ev.value & mask_as_int == ev.value)
+            .filter(|(_, ev)| ev.value & value_mask == ev.value)
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
@@ -48,13 +60,14 @@ pub fn parse(
             log::warn!("Invalid enumerated values dropped for field {}", name);
         }
         if !filtered_values.is_empty() {
-            chip::ValueRestriction::Enumerated(filtered_values)
+            chip::ValueRestriction::Enumerated {
+                group: Some(id.clone()),
+                values: filtered_values,
+            }
         } else {
             log::warn!("Empty enumerated values for field {}", name);
             chip::ValueRestriction::Unsafe
         }
-    } else if unsafe_range {
-        chip::ValueRestriction::Unsafe
     } else {
         chip::ValueRestriction::Any
     };
@@ -80,11 +93,125 @@ pub fn parse(
         chip::AccessMode::ReadWrite
     };
 
-    Ok(chip::Field {
-        name,
-        description,
-        range,
-        access,
-        restriction,
-    })
+    if let (Some(dim), [(_, msb)]) = (dim, runs.as_slice()) {
+        let register_bits = (register_size * 8).min(64);
+        if msb + (dim.count - 1) * dim.increment >= register_bits {
+            return Err(atdf::error::UnsupportedError::new(
+                format!(
+                    "{} elements of mask {:?} exceeding the register",
+                    dim.count, mask
+                ),
+                bitfield_el,
+            )
+            .into());
+        }
+    }
+
+    if let [range] = runs.as_slice() {
+        return Ok(vec![chip::Field {
+            name,
+            description,
+            range: *range,
+            access,
+            restriction,
+            part: None,
+            dim,
+        }]);
+    }
+
+    if dim.is_some() {
+        return Err(atdf::error::UnsupportedError::new(
+            format!("count on non-contiguous mask {:?}", mask),
+            bitfield_el,
+        )
+        .into());
+    }
+
+    let mut fields = vec![];
+    let mut offset = 0;
+    for (i, range) in runs.into_iter().enumerate() {
+        let part_width = range.1 - range.0 + 1;
+        let bits = if part_width == 1 {
+            format!("bit {offset}")
+        } else {
+            format!("bits {}:{}", offset + part_width - 1, offset)
+        };
+
+        fields.push(chip::Field {
+            name: format!("{name}_{i}"),
+            description: Some(match description {
+                Some(ref d) => format!("{d} ({bits} of {name})"),
+                None => format!("{} of {}", bits, name),
+            }),
+            range,
+            access,
+            restriction: part_restriction(&restriction, offset, part_width),
+            part: Some(chip::FieldPart {
+                bitfield: name.clone(),
+                offset,
+            }),
+            dim: None,
+        });
+        offset += part_width;
+    }
+
+    Ok(fields)
+}
+
+/// Restriction of a part of a non-contiguous bitfield
+///
+/// Enumerated values only make sense for the whole bitfield, so a part is unrestricted if all
+/// of its values appear in some enumerated value and unsafe otherwise.
+fn part_restriction(
+    restriction: &chip::ValueRestriction,
+    offset: usize,
+    width: usize,
+) -> chip::ValueRestriction {
+    match restriction {
+        chip::ValueRestriction::Enumerated { values, .. } => {
+            let part_mask = (1 << width) - 1;
+            let covered: std::collections::BTreeSet<_> = values
+                .values()
+                .map(|ev| (ev.value >> offset) & part_mask)
+                .collect();
+            if covered.len() == part_mask + 1 {
+                chip::ValueRestriction::Any
+            } else {
+                chip::ValueRestriction::Unsafe
+            }
+        }
+        r => r.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_bitfield(xml: &str, register_size: usize) -> crate::Result<Vec<chip::Field>> {
+        let el = xmltree::Element::parse(xml.as_bytes())?;
+        parse(&el, register_size, &Default::default())
+    }
+
+    #[test]
+    fn bitfield_count() {
+        let fields = parse_bitfield(r#"<bitfield name="PMUX" mask="0x3" count="4"/>"#, 1)
+            .unwrap_or_else(|e| e.to_panic());
+        let [field] = fields.as_slice() else {
+            panic!("expected a single field array");
+        };
+        assert_eq!(field.range, (0, 1));
+        assert_eq!(
+            field.dim,
+            Some(chip::Dim {
+                count: 4,
+                increment: 2
+            })
+        );
+        assert_eq!(field.mask(), 0xff);
+
+        // Elements reaching beyond the register are rejected
+        assert!(parse_bitfield(r#"<bitfield name="PMUX" mask="0x3" count="5"/>"#, 1).is_err());
+        assert!(parse_bitfield(r#"<bitfield name="EN" mask="0x2" count="64"/>"#, 8).is_err());
+    }
 }
@@ -1,15 +1,52 @@
 use crate::ElementExt;
+use crate::atdf;
 use crate::chip;
 use crate::util;
+use std::collections::BTreeMap;
+
+/// What to do with interrupts sharing the same index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Append the name of the later interrupt (without its module prefix) to the first one
+    #[default]
+    Concatenate,
+    /// Keep the first interrupt and drop all others
+    First,
+    /// Keep the first interrupt and list the others in its description
+    Aliases,
+    /// Treat colliding interrupts as an error
+    Fail,
+}
+
+impl std::str::FromStr for CollisionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "concatenate" => Ok(CollisionPolicy::Concatenate),
+            "first" => Ok(CollisionPolicy::First),
+            "aliases" => Ok(CollisionPolicy::Aliases),
+            "fail" => Ok(CollisionPolicy::Fail),
+            _ => Err(format!(
+                "unknown interrupt collision policy {s:?}, use `concatenate`, `first`, `aliases` or `fail`"
+            )),
+        }
+    }
+}
 
 pub fn parse(interrupt: &xmltree::Element) -> crate::Result<chip::Interrupt> {
     debug_assert!(interrupt.name == "interrupt");
 
+    let module_instance = interrupt.attributes.get("module-instance").cloned();
+    // SAM interrupts are named after their instance already, those must not get the prefix twice
     let name = {
         let inst_name = interrupt.attr("name")?;
-        interrupt
-            .attr("module-instance")
-            .map_or_else(|_| inst_name.clone(), |s| format!("{}_{}", s, inst_name))
+        match module_instance {
+            Some(ref s) if inst_name != s && !inst_name.starts_with(&format!("{s}_")) => {
+                format!("{}_{}", s, inst_name)
+            }
+            _ => inst_name.clone(),
+        }
     };
     let index = util::parse_int(interrupt.attr("index")?)?;
     let description = interrupt
@@ -22,5 +59,118 @@ pub fn parse(interrupt: &xmltree::Element) -> crate::Result<chip::Interrupt> {
         name,
         description,
         index,
+        module_instance,
     })
 }
+
+/// Parse all `<interrupt>`s of a device, resolving shared indices according to `policy`
+pub fn parse_list(
+    el: &xmltree::Element,
+    policy: CollisionPolicy,
+) -> crate::Result<BTreeMap<String, chip::Interrupt>> {
+    let mut interrupts = BTreeMap::<usize, chip::Interrupt>::new();
+    for interrupt_el in el.iter_children_with_name("interrupt", Some("interrupts")) {
+        // Cortex-M system exceptions have negative indices, they are not part of the device
+        if interrupt_el.attr("index")?.starts_with('-') {
+            log::debug!("Skipping system exception {}", interrupt_el.attr("name")?);
+            continue;
+        }
+
+        let int = parse(interrupt_el)?;
+        let Some(existing_int) = interrupts.get_mut(&int.index) else {
+            interrupts.insert(int.index, int);
+            continue;
+        };
+
+        match policy {
+            CollisionPolicy::Concatenate => {
+                let old_name = existing_int.name.clone();
+                if let Some(split_idx) = int.name.find('_') {
+                    existing_int.name.push_str(int.name.split_at(split_idx).1);
+                } else {
+                    existing_int.name.push('_');
+                    existing_int.name.push_str(&int.name);
+                }
+                log::warn!(
+                    "Merging interrupt {} and {} to {}",
+                    old_name,
+                    int.name,
+                    existing_int.name
+                );
+            }
+            CollisionPolicy::First => log::warn!(
+                "Dropping interrupt {} which shares index {} with {}",
+                int.name,
+                int.index,
+                existing_int.name
+            ),
+            CollisionPolicy::Aliases => {
+                let alias = match int.description {
+                    Some(ref d) => format!("{}: {}", int.name, d),
+                    None => int.name.clone(),
+                };
+                existing_int.description = Some(match existing_int.description {
+                    Some(ref d) => format!("{d}; alias {alias}"),
+                    None => format!("{}; alias {}", existing_int.name, alias),
+                });
+            }
+            CollisionPolicy::Fail => {
+                return Err(atdf::error::UnsupportedError::new(
+                    format!("sharing index {} with {}", int.index, existing_int.name),
+                    interrupt_el,
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok(interrupts
+        .into_values()
+        .map(|int| (int.name.clone(), int))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_interrupts(policy: CollisionPolicy) -> crate::Result<Vec<chip::Interrupt>> {
+        let el = xmltree::Element::parse(
+            r#"<interrupts>
+                <interrupt index="1" name="INT0" caption="External Interrupt 0"/>
+                <interrupt index="2" name="TXC" module-instance="USART0"/>
+                <interrupt index="2" name="RXC" module-instance="USART0" caption="Receive Complete"/>
+                <interrupt index="2" name="UDRE" module-instance="USART0"/>
+            </interrupts>"#
+                .as_bytes(),
+        )?;
+        let mut interrupts: Vec<_> = parse_list(&el, policy)?.into_values().collect();
+        interrupts.sort_by_key(|i| i.index);
+        Ok(interrupts)
+    }
+
+    #[test]
+    fn collision_first() {
+        let interrupts = parse_interrupts(CollisionPolicy::First).unwrap_or_else(|e| e.to_panic());
+        let names: Vec<_> = interrupts.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["INT0", "USART0_TXC"]);
+        assert_eq!(interrupts[1].description, None);
+    }
+
+    #[test]
+    fn collision_aliases() {
+        let interrupts =
+            parse_interrupts(CollisionPolicy::Aliases).unwrap_or_else(|e| e.to_panic());
+        let names: Vec<_> = interrupts.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["INT0", "USART0_TXC"]);
+        assert_eq!(
+            interrupts[1].description.as_deref(),
+            Some("USART0_TXC; alias USART0_RXC: Receive Complete; alias USART0_UDRE")
+        );
+    }
+
+    #[test]
+    fn collision_fail() {
+        assert!(parse_interrupts(CollisionPolicy::Fail).is_err());
+    }
+}
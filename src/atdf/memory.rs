@@ -0,0 +1,55 @@
+use crate::ElementExt;
+use crate::atdf;
+use crate::chip;
+use crate::util;
+
+fn parse_segment(el: &xmltree::Element) -> crate::Result<chip::MemorySegment> {
+    debug_assert!(el.name == "memory-segment");
+
+    Ok(chip::MemorySegment {
+        name: el.attr("name")?.clone(),
+        kind: el.attr("type")?.clone(),
+        start: util::parse_int(el.attr("start")?)?,
+        size: util::parse_int(el.attr("size")?)?,
+        page_size: el
+            .attributes
+            .get("pagesize")
+            .map(|p| util::parse_int(p))
+            .transpose()?,
+        access: el
+            .attributes
+            .get("rw")
+            .map_or(chip::AccessMode::ReadWrite, |a| {
+                atdf::register::parse_access(a)
+            }),
+        executable: el
+            .attributes
+            .get("exec")
+            .is_some_and(|e| e == "1" || e == "true"),
+        external: el.attributes.get("external").is_some_and(|e| e == "true"),
+    })
+}
+
+pub fn parse(el: &xmltree::Element) -> crate::Result<chip::AddressSpace> {
+    debug_assert!(el.name == "address-space");
+
+    let endianness = match el.attributes.get("endianness").map(String::as_str) {
+        Some("little") => Some(chip::Endianness::Little),
+        Some("big") => Some(chip::Endianness::Big),
+        Some(e) => {
+            return Err(atdf::error::UnsupportedError::new(format!("endianness {e:?}"), el).into());
+        }
+        None => None,
+    };
+
+    Ok(chip::AddressSpace {
+        name: el.attr("id").or_else(|_| el.attr("name"))?.clone(),
+        start: util::parse_int(el.attr("start")?)?,
+        size: util::parse_int(el.attr("size")?)?,
+        endianness,
+        segments: el
+            .iter_children_with_name("memory-segment", Some("address-space"))
+            .map(parse_segment)
+            .collect::<Result<_, _>>()?,
+    })
+}
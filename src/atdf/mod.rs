@@ -4,33 +4,82 @@ pub mod chip;
 pub mod error;
 pub mod field;
 pub mod interrupt;
+pub mod memory;
+pub mod pack;
 pub mod patch;
 pub mod peripheral;
+pub mod pinout;
+pub mod property;
 pub mod register;
 pub mod values;
+pub mod variant;
+
+pub use interrupt::CollisionPolicy;
 
 pub fn parse<R: std::io::Read>(
     r: R,
     patches: &HashSet<String>,
+) -> crate::Result<crate::chip::Chip> {
+    parse_with_collision_policy(r, patches, CollisionPolicy::default())
+}
+
+/// Parse an ATDF file, resolving interrupts which share an index according to `policy`
+pub fn parse_with_collision_policy<R: std::io::Read>(
+    r: R,
+    patches: &HashSet<String>,
+    interrupt_collisions: CollisionPolicy,
 ) -> crate::Result<crate::chip::Chip> {
     let tree = xmltree::Element::parse(r)?;
 
-    let mut chip = chip::parse(&tree)?;
+    let mut chip = chip::parse(&tree, interrupt_collisions)?;
+    apply_patches(&mut chip, &tree, patches)?;
 
-    patch::signals_to_port_fields(&mut chip, &tree)
+    Ok(chip)
+}
+
+/// Parse all devices defined in an ATDF file
+pub fn parse_all<R: std::io::Read>(
+    r: R,
+    patches: &HashSet<String>,
+) -> crate::Result<Vec<crate::chip::Chip>> {
+    parse_all_with_collision_policy(r, patches, CollisionPolicy::default())
+}
+
+/// Parse all devices defined in an ATDF file, resolving shared interrupt indices by `policy`
+pub fn parse_all_with_collision_policy<R: std::io::Read>(
+    r: R,
+    patches: &HashSet<String>,
+    interrupt_collisions: CollisionPolicy,
+) -> crate::Result<Vec<crate::chip::Chip>> {
+    let tree = xmltree::Element::parse(r)?;
+
+    let mut chips = chip::parse_all(&tree, interrupt_collisions)?;
+    for chip in chips.iter_mut() {
+        apply_patches(chip, &tree, patches)?;
+    }
+
+    Ok(chips)
+}
+
+fn apply_patches(
+    chip: &mut crate::chip::Chip,
+    tree: &xmltree::Element,
+    patches: &HashSet<String>,
+) -> crate::Result<()> {
+    patch::signals_to_port_fields(chip, tree)
         .unwrap_or_else(|_| log::warn!("Could not apply 'signals_to_port_fields' patch!"));
 
     if !patches.contains("keep_unsafe_cpu_registers") {
-        patch::remove_unsafe_cpu_regs(&mut chip, &tree)?;
+        patch::remove_unsafe_cpu_regs(chip, tree)?;
     }
 
     if patches.contains("remove_fuse_and_lockbit") {
-        patch::remove_fuse_and_lockbit(&mut chip, &tree)?;
+        patch::remove_fuse_and_lockbit(chip, tree)?;
     }
 
     if patches.contains("remove_register_common_prefix") {
-        patch::remove_register_common_prefix(&mut chip)?;
+        patch::remove_register_common_prefix(chip)?;
     }
 
-    Ok(chip)
+    Ok(())
 }
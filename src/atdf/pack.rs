@@ -0,0 +1,32 @@
+use crate::ElementExt;
+use std::path::Path;
+
+/// Find the version of the device family pack an ATDF file is part of
+///
+/// Packs keep their ATDF files in an `atdf/` directory next to the `.pdsc` package description,
+/// whose first `<release>` is the current version.
+pub fn version(atdf_path: &Path) -> Option<String> {
+    // Relative paths like `atdf/X.atdf` have no usable grandparent
+    let atdf_path = std::fs::canonicalize(atdf_path).ok()?;
+    let pdsc = atdf_path
+        .ancestors()
+        .skip(1)
+        .take(2)
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|p| p.extension().is_some_and(|e| e == "pdsc"))?;
+
+    let parse = || -> crate::Result<String> {
+        let tree = xmltree::Element::parse(std::fs::File::open(&pdsc)?)?;
+        Ok(tree
+            .first_child("releases")?
+            .first_child("release")?
+            .attr("version")?
+            .clone())
+    };
+    parse()
+        .inspect_err(|_| log::warn!("Could not read pack version from {}", pdsc.display()))
+        .ok()
+}
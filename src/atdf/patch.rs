@@ -11,7 +11,7 @@ const NEW_PORT_REGS: [&str; 10] = [
 pub fn signals_to_port_fields(chip: &mut chip::Chip, tree: &xmltree::Element) -> crate::Result<()> {
     let port_module = tree
         .first_child("devices")?
-        .first_child("device")?
+        .first_child_by_attr(Some("device"), "name", &chip.name)?
         .first_child("peripherals")?
         .first_child_by_attr(Some("module"), "name", "PORT")?;
 
@@ -40,6 +40,8 @@ pub fn signals_to_port_fields(chip: &mut chip::Chip, tree: &xmltree::Element) ->
                 range: (p, p),
                 access: chip::AccessMode::ReadWrite,
                 restriction: chip::ValueRestriction::Any,
+                part: None,
+                dim: None,
             })
             .map(|f| (f.name.clone(), f))
             .collect();
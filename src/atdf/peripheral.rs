@@ -2,6 +2,214 @@ use crate::ElementExt;
 use crate::atdf;
 use crate::chip;
 use crate::util;
+use std::collections::BTreeMap;
+
+fn register_map(registers: Vec<chip::Register>) -> BTreeMap<String, chip::Register> {
+    registers
+        .into_iter()
+        .map(|r| {
+            (
+                match r.mode {
+                    Some(ref mode) => format!("{mode}_{}", r.name),
+                    _ => r.name.clone(),
+                },
+                r,
+            )
+        })
+        .collect()
+}
+
+/// Registers and clusters making up a register-group
+#[derive(Default)]
+struct GroupContents {
+    registers: Vec<chip::Register>,
+    clusters: Vec<chip::Cluster>,
+}
+
+/// Parse a register-group and all groups it references
+///
+/// `parents` are the names of the groups currently being resolved, a group referencing one of
+/// them would never end.
+fn parse_group(
+    group: &xmltree::Element,
+    module: &xmltree::Element,
+    address_space: &str,
+    offset: usize,
+    value_groups: &atdf::values::ValueGroups,
+    parents: &[&str],
+) -> crate::Result<GroupContents> {
+    let name = group.attr("name")?.as_str();
+    if parents.contains(&name) {
+        return Err(atdf::error::UnsupportedError::new(
+            format!("register-group {name:?} referencing itself"),
+            group,
+        )
+        .into());
+    }
+    let parents = &[parents, &[name]].concat();
+
+    if group.attributes.get("class").map(String::as_str) == Some("union") {
+        return Ok(GroupContents {
+            registers: vec![],
+            clusters: parse_union(group, module, address_space, offset, value_groups, parents)?,
+        });
+    }
+
+    let modes = atdf::register::parse_modes(group)?;
+
+    let mut contents = GroupContents::default();
+    for child in group.children.iter().filter_map(|node| node.as_element()) {
+        match child.name.as_str() {
+            "register" => contents.registers.extend(atdf::register::parse(
+                child,
+                address_space,
+                offset,
+                value_groups,
+                &modes,
+            )?),
+            "register-group" => contents.clusters.push(parse_nested(
+                child,
+                module,
+                address_space,
+                offset,
+                value_groups,
+                parents,
+            )?),
+            // Already handled above
+            "mode" => (),
+            _ => log::warn!(
+                "Unhandled child element in <register-group>: {}",
+                child.debug()
+            ),
+        }
+    }
+    Ok(contents)
+}
+
+/// Parse a `class="union"` register-group into one cluster per union member
+///
+/// All members live at the same address and the `union-tag` names the field selecting which of
+/// them is active.  The first member is the primary view, all others are alternates of it.
+fn parse_union(
+    group: &xmltree::Element,
+    module: &xmltree::Element,
+    address_space: &str,
+    offset: usize,
+    value_groups: &atdf::values::ValueGroups,
+    parents: &[&str],
+) -> crate::Result<Vec<chip::Cluster>> {
+    let union_tag = group.attributes.get("union-tag");
+
+    let mut clusters: Vec<chip::Cluster> = vec![];
+    for member in group.iter_children_with_name("register-group", Some("register-group")) {
+        let name = member.attr("name")?.clone();
+        let address = offset + util::parse_int(member.attr("offset")?)?;
+
+        let member_group = module.first_child_by_attr(
+            Some("register-group"),
+            "name",
+            member.attr("name-in-module")?,
+        )?;
+
+        let caption = member_group
+            .attributes
+            .get("caption")
+            .and_then(|d| if !d.is_empty() { Some(d) } else { None })
+            .cloned();
+        let contents = parse_group(
+            member_group,
+            module,
+            address_space,
+            address,
+            value_groups,
+            parents,
+        )?;
+        let description = match (union_tag, member.attributes.get("union-tag-value")) {
+            (Some(tag), Some(value)) => Some(format!(
+                "{} (active when {})",
+                caption.unwrap_or_else(|| name.clone()),
+                atdf::register::describe_condition(tag, value)
+            )),
+            _ => caption,
+        };
+
+        clusters.push(chip::Cluster {
+            alternate: clusters.first().map(|c| c.name.clone()),
+            name,
+            description,
+            address,
+            dim: None,
+            registers: register_map(contents.registers),
+            clusters: contents
+                .clusters
+                .into_iter()
+                .map(|c| (c.name.clone(), c))
+                .collect(),
+        });
+    }
+
+    Ok(clusters)
+}
+
+/// Parse a register-group referencing another register-group of the module into a cluster
+///
+/// A `count` repeats the referenced group every `size` bytes, which makes the cluster an array.
+fn parse_nested(
+    reference: &xmltree::Element,
+    module: &xmltree::Element,
+    address_space: &str,
+    offset: usize,
+    value_groups: &atdf::values::ValueGroups,
+    parents: &[&str],
+) -> crate::Result<chip::Cluster> {
+    let name = reference.attr("name")?.clone();
+    let address = offset + util::parse_int(reference.attr("offset")?)?;
+    let group = module.first_child_by_attr(
+        Some("register-group"),
+        "name",
+        reference.attr("name-in-module")?,
+    )?;
+
+    let count = match reference.attributes.get("count") {
+        Some(count) => util::parse_int(count)?,
+        None => 1,
+    };
+    let dim = if count > 1 {
+        let size = reference
+            .attributes
+            .get("size")
+            .or_else(|| group.attributes.get("size"))
+            .ok_or_else(|| {
+                atdf::error::UnsupportedError::new("a count without a size", reference)
+            })?;
+        Some(chip::Dim {
+            count,
+            increment: util::parse_int(size)?,
+        })
+    } else {
+        None
+    };
+
+    let contents = parse_group(group, module, address_space, address, value_groups, parents)?;
+    Ok(chip::Cluster {
+        name,
+        description: reference
+            .attributes
+            .get("caption")
+            .or_else(|| group.attributes.get("caption"))
+            .and_then(|d| if !d.is_empty() { Some(d) } else { None })
+            .cloned(),
+        address,
+        alternate: None,
+        dim,
+        registers: register_map(contents.registers),
+        clusters: contents
+            .clusters
+            .into_iter()
+            .map(|c| (c.name.clone(), c))
+            .collect(),
+    })
+}
 
 pub fn parse_list(
     el: &xmltree::Element,
@@ -14,6 +222,8 @@ pub fn parse_list(
 
         for instance in module.iter_children_with_name("instance", Some("module")) {
             let mut registers = vec![];
+            let mut clusters = vec![];
+            let mut address_space: Option<&str> = None;
 
             // Find corresponding module
             let module = modules.first_child_by_attr(Some("module"), "name", module_name)?;
@@ -30,25 +240,43 @@ pub fn parse_list(
                 let name = register_group.attr("name-in-module")?;
                 let offset = util::parse_int(register_group.attr("offset")?)?;
 
+                // Offsets are relative to the address space the register-group is located in.
+                // Registers which are not memory-mapped live in spaces like `fuses` or
+                // `signatures` instead of the `data` space.
+                let group_space = register_group
+                    .attributes
+                    .get("address-space")
+                    .map_or("data", String::as_str);
+                match address_space {
+                    None => address_space = Some(group_space),
+                    Some(space) if space != group_space => log::warn!(
+                        "Peripheral {} has registers in address spaces {} and {}",
+                        instance.attr("name")?,
+                        space,
+                        group_space
+                    ),
+                    Some(_) => (),
+                }
+
                 let group = module.first_child_by_attr(Some("register-group"), "name", name)?;
 
-                for register in group.iter_children_with_name("register", Some("register-group")) {
-                    registers.push(atdf::register::parse(register, offset, &value_groups)?);
-                }
+                let contents = parse_group(group, module, group_space, offset, &value_groups, &[])?;
+                registers.extend(contents.registers);
+                clusters.extend(contents.clusters);
             }
 
-            let registers = registers
-                .into_iter()
-                .map(|r| {
-                    (
-                        match r.mode {
-                            Some(ref mode) => format!("{mode}_{}", r.name),
-                            _ => r.name.clone(),
-                        },
-                        r,
-                    )
-                })
-                .collect();
+            // Parameters are given either for the whole module or for each instance, the latter
+            // taking precedence.
+            let mut parameters = BTreeMap::new();
+            for el in [
+                module.get_child("parameters"),
+                instance.get_child("parameters"),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                parameters.append(&mut atdf::property::parse_parameters(el)?);
+            }
 
             peripherals.push(chip::Peripheral {
                 name: instance.attr("name")?.clone(),
@@ -58,10 +286,54 @@ pub fn parse_list(
                     .ok()
                     .cloned()
                     .and_then(|d| if !d.is_empty() { Some(d) } else { None }),
-                registers,
+                module: module_name.clone(),
+                address_space: address_space.unwrap_or("data").to_string(),
+                registers: register_map(registers),
+                clusters: clusters.into_iter().map(|c| (c.name.clone(), c)).collect(),
+                signals: match instance.get_child("signals") {
+                    Some(signals) => atdf::pinout::parse_signals(signals),
+                    None => vec![],
+                },
+                parameters,
             })
         }
     }
 
     Ok(peripherals)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_group_cycle() {
+        let peripherals = xmltree::Element::parse(
+            r#"<peripherals>
+                <module name="M">
+                    <instance name="M">
+                        <register-group name="M" name-in-module="A" offset="0x0"/>
+                    </instance>
+                </module>
+            </peripherals>"#
+                .as_bytes(),
+        )
+        .unwrap();
+        let modules = xmltree::Element::parse(
+            r#"<modules>
+                <module name="M">
+                    <register-group name="A">
+                        <register-group name="B" name-in-module="B" offset="0x0"/>
+                    </register-group>
+                    <register-group name="B">
+                        <register-group name="A" name-in-module="A" offset="0x10"/>
+                    </register-group>
+                </module>
+            </modules>"#
+                .as_bytes(),
+        )
+        .unwrap();
+
+        assert!(parse_list(&peripherals, &modules).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::ElementExt;
+use crate::chip;
+use crate::util;
+
+pub fn parse(el: &xmltree::Element) -> crate::Result<chip::Pinout> {
+    debug_assert!(el.name == "pinout");
+
+    let pins = el
+        .iter_children_with_name("pin", Some("pinout"))
+        .map(|p| {
+            Ok(chip::Pin {
+                position: p.attr("position")?.clone(),
+                pad: p.attr("pad")?.clone(),
+            })
+        })
+        .collect::<crate::Result<_>>()?;
+
+    Ok(chip::Pinout {
+        name: el.attr("name")?.clone(),
+        description: el
+            .attributes
+            .get("caption")
+            .and_then(|d| if !d.is_empty() { Some(d) } else { None })
+            .cloned(),
+        pins,
+    })
+}
+
+fn parse_signal(s: &xmltree::Element) -> crate::Result<chip::Signal> {
+    Ok(chip::Signal {
+        group: s.attr("group")?.clone(),
+        index: s
+            .attributes
+            .get("index")
+            .map(|i| util::parse_int(i))
+            .transpose()?,
+        function: s.attr("function")?.clone(),
+        pad: s.attr("pad")?.clone(),
+        field: s.attributes.get("field").cloned(),
+    })
+}
+
+/// Parse the `<signals>` of a peripheral instance
+///
+/// Signals are informational only, malformed ones are skipped with a warning instead of failing
+/// the whole conversion.
+pub fn parse_signals(el: &xmltree::Element) -> Vec<chip::Signal> {
+    el.iter_children_with_name("signal", Some("signals"))
+        .filter_map(|s| {
+            parse_signal(s)
+                .inspect_err(|_| log::warn!("Skipping malformed signal {}", s.debug()))
+                .ok()
+        })
+        .collect()
+}
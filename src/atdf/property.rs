@@ -0,0 +1,136 @@
+use crate::ElementExt;
+use crate::chip;
+use crate::util;
+use std::collections::BTreeMap;
+
+pub fn parse_value(value: &str) -> chip::PropertyValue {
+    match util::parse_int(value) {
+        Ok(i) => chip::PropertyValue::Integer(i),
+        Err(_) => chip::PropertyValue::String(value.to_string()),
+    }
+}
+
+pub fn parse_groups(el: &xmltree::Element) -> crate::Result<chip::PropertyGroups> {
+    // Structure: <property-groups>
+    //                <property-group name="...">
+    //                    <property name="..." value="..." />
+    //                    ...
+    //                </property-group>
+    //                ...
+    //            </property-groups>
+
+    let mut groups = BTreeMap::new();
+    for group_el in el.iter_children_with_name("property-group", Some("property-groups")) {
+        let properties = group_el
+            .iter_children_with_name("property", Some("property-group"))
+            .map(|p| Ok((p.attr("name")?.clone(), parse_value(p.attr("value")?))))
+            .collect::<crate::Result<_>>()?;
+
+        groups.insert(group_el.attr("name")?.clone(), properties);
+    }
+
+    Ok(groups)
+}
+
+pub fn parse_parameters(el: &xmltree::Element) -> crate::Result<chip::Parameters> {
+    el.iter_children_with_name("param", Some("parameters"))
+        .map(|p| Ok((p.attr("name")?.clone(), parse_value(p.attr("value")?))))
+        .collect()
+}
+
+pub fn parse_interfaces(el: &xmltree::Element) -> crate::Result<Vec<chip::Interface>> {
+    el.iter_children_with_name("interface", Some("interfaces"))
+        .map(|i| {
+            Ok(chip::Interface {
+                name: i.attr("name")?.clone(),
+                kind: i.attr("type")?.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Decode the device signature from the `SIGNATURES` property-group
+pub fn signature(groups: &chip::PropertyGroups) -> crate::Result<Option<Vec<u8>>> {
+    let Some(signatures) = groups.get("SIGNATURES") else {
+        return Ok(None);
+    };
+
+    let mut signature = vec![];
+    while let Some(value) = signatures.get(&format!("SIGNATURE{}", signature.len())) {
+        match value {
+            chip::PropertyValue::Integer(i) => signature.push(u8::try_from(*i)?),
+            chip::PropertyValue::String(s) => {
+                log::warn!("Invalid signature byte {s:?}");
+                return Ok(None);
+            }
+        }
+    }
+
+    Ok(if signature.is_empty() {
+        None
+    } else {
+        Some(signature)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_property_groups(xml: &str) -> chip::PropertyGroups {
+        parse_groups(&xmltree::Element::parse(xml.as_bytes()).unwrap())
+            .unwrap_or_else(|e| e.to_panic())
+    }
+
+    #[test]
+    fn signature_bytes() {
+        let groups = parse_property_groups(
+            r#"<property-groups>
+                <property-group name="SIGNATURES">
+                    <property name="SIGNATURE0" value="0x1e"/>
+                    <property name="SIGNATURE1" value="0x95"/>
+                    <property name="SIGNATURE2" value="0x0f"/>
+                </property-group>
+            </property-groups>"#,
+        );
+        assert_eq!(
+            signature(&groups).unwrap_or_else(|e| e.to_panic()),
+            Some(vec![0x1e, 0x95, 0x0f])
+        );
+    }
+
+    #[test]
+    fn signature_missing() {
+        let groups = parse_property_groups(
+            r#"<property-groups>
+                <property-group name="OCD">
+                    <property name="OCD_REVISION" value="1"/>
+                </property-group>
+            </property-groups>"#,
+        );
+        assert_eq!(signature(&groups).unwrap_or_else(|e| e.to_panic()), None);
+    }
+
+    #[test]
+    fn signature_invalid_byte() {
+        let groups = parse_property_groups(
+            r#"<property-groups>
+                <property-group name="SIGNATURES">
+                    <property name="SIGNATURE0" value="0x1e"/>
+                    <property name="SIGNATURE1" value="unknown"/>
+                </property-group>
+            </property-groups>"#,
+        );
+        assert_eq!(signature(&groups).unwrap_or_else(|e| e.to_panic()), None);
+
+        let groups = parse_property_groups(
+            r#"<property-groups>
+                <property-group name="SIGNATURES">
+                    <property name="SIGNATURE0" value="0x1e"/>
+                    <property name="SIGNATURE1" value="0x195"/>
+                </property-group>
+            </property-groups>"#,
+        );
+        assert!(signature(&groups).is_err());
+    }
+}
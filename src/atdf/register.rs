@@ -4,24 +4,125 @@ use crate::chip;
 use crate::util;
 use std::collections::BTreeMap;
 
+/// A `<mode>` of a register or register-group
+///
+/// Modes are selected by the value of another field, the `qualifier`.
+#[derive(Debug, Clone)]
+pub struct Mode {
+    pub name: String,
+    pub qualifier: Option<String>,
+    pub value: Option<String>,
+}
+
+impl Mode {
+    /// Human readable explanation of this mode and when it is active
+    pub fn describe(&self) -> String {
+        match (&self.qualifier, &self.value) {
+            (Some(qualifier), Some(value)) => format!(
+                "{} mode, active when {}",
+                self.name,
+                describe_condition(qualifier, value)
+            ),
+            _ => format!("{} mode", self.name),
+        }
+    }
+}
+
+/// Describe the condition `qualifier == value` for use in descriptions
+pub fn describe_condition(qualifier: &str, value: &str) -> String {
+    // Qualifiers look like `MODULE[.GROUP].REGISTER.FIELD`, only register and field are
+    // interesting to the reader.
+    let parts: Vec<_> = qualifier.rsplitn(3, '.').collect();
+    let target = match parts.as_slice() {
+        [field, register, ..] => format!("{register}.{field}"),
+        _ => qualifier.to_string(),
+    };
+
+    let values: Vec<_> = value.split_whitespace().collect();
+    if values.len() > 1 {
+        format!("{} is one of {}", target, values.join(", "))
+    } else {
+        format!("{target} = {value}")
+    }
+}
+
+/// Parse all `<mode>` children of a register or register-group
+pub fn parse_modes(el: &xmltree::Element) -> crate::Result<Vec<Mode>> {
+    el.children
+        .iter()
+        .filter_map(|node| node.as_element().filter(|e| e.name == "mode"))
+        .map(|m| {
+            Ok(Mode {
+                name: m.attr("name")?.clone(),
+                qualifier: m.attributes.get("qualifier").cloned(),
+                value: m.attributes.get("value").cloned(),
+            })
+        })
+        .collect()
+}
+
 fn field_map_from_bitfield_children(
     el: &xmltree::Element,
+    register_size: usize,
     values: &atdf::values::ValueGroups,
     mode_name: Option<&str>,
 ) -> crate::Result<BTreeMap<String, chip::Field>> {
+    // Bitfields can be restricted to certain modes using the `modes` attribute.  Those without
+    // it belong to all modes.
     el.children
         .iter()
         .filter_map(|node| node.as_element().filter(|e| e.name == "bitfield"))
-        .map(|e| atdf::field::parse(e, values, mode_name))
-        .map(|r| r.map(|f| (f.name.clone(), f)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
+        .filter(|e| match (e.attributes.get("modes"), mode_name) {
+            (Some(modes), Some(mode_name)) => modes.split_whitespace().any(|m| m == mode_name),
+            _ => true,
+        })
+        .map(|e| atdf::field::parse(e, register_size, values))
+        .collect::<Result<Vec<_>, _>>()
+        .map(|fields| {
+            fields
+                .into_iter()
+                .flatten()
+                .map(|f| (f.name.clone(), f))
+                .collect()
+        })
 }
 
+pub fn parse_access(access: &str) -> chip::AccessMode {
+    match access {
+        "" => chip::AccessMode::NoAccess,
+        "R" => chip::AccessMode::ReadOnly,
+        "W" => chip::AccessMode::WriteOnly,
+        "RW" => chip::AccessMode::ReadWrite,
+        _ => chip::AccessMode::ReadWrite,
+    }
+}
+
+fn append_description(description: Option<String>, extra: Option<String>) -> Option<String> {
+    match (description, extra) {
+        (Some(d), Some(extra)) => Some(format!("{d} ({extra})")),
+        (d, None) => d,
+        (None, extra) => extra,
+    }
+}
+
+/// Describe `mode` unless it is the only one of `modes` and has no condition (like a lone
+/// `DEFAULT` mode)
+fn describe_mode(mode: &Mode, modes: &[Mode]) -> Option<String> {
+    (modes.len() > 1 || mode.qualifier.is_some()).then(|| mode.describe())
+}
+
+/// Parse a register
+///
+/// A register with several `<mode>`s results in one register per mode.  The first one is the
+/// primary register while all others are alternates of it.  `group_modes` are the modes
+/// defined on the surrounding register-group.
 pub fn parse(
     el: &xmltree::Element,
+    address_space: &str,
     offset: usize,
     values: &atdf::values::ValueGroups,
-) -> crate::Result<chip::Register> {
+    group_modes: &[Mode],
+) -> crate::Result<Vec<chip::Register>> {
     let name = el.attr("name")?.clone();
 
     let description = el
@@ -36,51 +137,144 @@ pub fn parse(
         .and_then(|d| if !d.is_empty() { Some(d) } else { None })
         .cloned();
 
-    let access = if let Some(access) = el.attributes.get("rw") {
-        match access.as_ref() {
-            "" => chip::AccessMode::NoAccess,
-            "R" => chip::AccessMode::ReadOnly,
-            "W" => chip::AccessMode::WriteOnly,
-            "RW" => chip::AccessMode::ReadWrite,
-            _ => chip::AccessMode::ReadWrite,
-        }
+    let description = append_description(
+        description,
+        mode.as_ref()
+            .and_then(|mode| group_modes.iter().find(|m| &m.name == mode))
+            .and_then(|mode| describe_mode(mode, group_modes)),
+    );
+
+    let access = el
+        .attributes
+        .get("rw")
+        .map_or(chip::AccessMode::ReadWrite, |a| parse_access(a));
+
+    // `ocd-rw` restricts what the on-chip debugger may do with this register, usually because
+    // accessing it has side-effects (e.g. reading a data register pops a FIFO).
+    let ocd_access = el.attributes.get("ocd-rw").map(|a| parse_access(a));
+
+    let address = util::parse_int(el.attr("offset")?)? + offset;
+    let size = util::parse_int(el.attr("size")?)?;
+
+    // A `count` repeats the register back to back
+    let dim = match el.attributes.get("count") {
+        Some(count) => Some(util::parse_int(count)?)
+            .filter(|count| *count > 1)
+            .map(|count| chip::Dim {
+                count,
+                increment: size,
+            }),
+        None => None,
+    };
+
+    // Registers can specify which of their bits are actually implemented using the `mask`
+    // attribute.  Bits outside of this mask are not writable and do not have a reset value.
+    let mask = el
+        .attributes
+        .get("mask")
+        .map(|m| util::parse_int(m).and_then(|m| u64::try_from(m).map_err(Into::into)))
+        .transpose()?;
+
+    // The reset value is given by `initval`.  Registers without it are assumed to reset to zero.
+    let reset_mask = util::size_to_mask(size) & mask.unwrap_or(u64::MAX);
+    let reset_value = match el.attributes.get("initval") {
+        Some(initval) => u64::try_from(util::parse_int(initval)?)?,
+        None => 0,
+    };
+    let reset_value = if reset_value & !reset_mask != 0 {
+        log::warn!(
+            "Reset value {:#x} of register {} does not fit its implemented bits, truncating",
+            reset_value,
+            name
+        );
+        reset_value & reset_mask
     } else {
-        chip::AccessMode::ReadWrite
+        reset_value
     };
 
-    // get bitfield under register
-    let mut fields: BTreeMap<String, chip::Field> =
-        field_map_from_bitfield_children(el, values, None)?;
+    let make_register = |name: String,
+                         description: Option<String>,
+                         alternate: Option<String>,
+                         fields: BTreeMap<String, chip::Field>| {
+        if let Some(mask) = mask {
+            for field in fields.values() {
+                if field.mask() & !mask != 0 {
+                    log::warn!(
+                        "Field {} of register {} covers bits outside of the implemented mask {:#x}",
+                        field.name,
+                        name,
+                        mask
+                    );
+                }
+            }
+        }
 
-    // get bitfield under register.mode
-    el.children
-        .iter()
-        .filter_map(|mode| {
-            mode.as_element()
-                .filter(|m| m.name == "mode")
-                .and_then(|m| m.attributes.get("name").map(|mode_name| (mode_name, m)))
-        })
-        .try_for_each(|(mode_name, el)| {
+        let restriction = if !fields.is_empty() {
+            chip::ValueRestriction::Unsafe
+        } else {
+            match mask {
+                // Only the lower bits are implemented, so restrict writes to their range.
+                Some(mask) if mask.checked_add(1).is_some_and(u64::is_power_of_two) => {
+                    chip::ValueRestriction::Range(0, mask)
+                }
+                // Writing to the gaps in the mask is not allowed.
+                Some(_) => chip::ValueRestriction::Unsafe,
+                None => chip::ValueRestriction::Any,
+            }
+        };
+
+        chip::Register {
+            name,
+            description,
+            mode: mode.clone(),
+            alternate,
+            address_space: address_space.to_string(),
+            address,
+            size,
+            access,
+            ocd_access,
+            restriction,
+            mask,
+            reset_value,
+            reset_mask,
+            dim,
+            fields,
+        }
+    };
+
+    let modes = parse_modes(el)?;
+    if modes.is_empty() {
+        let fields = field_map_from_bitfield_children(el, size, values, None)?;
+        return Ok(vec![make_register(name, description, None, fields)]);
+    }
+
+    let mut registers: Vec<chip::Register> = vec![];
+    for mode in modes.iter() {
+        // Bitfields of a mode are either direct children marked with the mode's name or they
+        // are nested inside of the `<mode>` element itself.
+        let mut fields = field_map_from_bitfield_children(el, size, values, Some(&mode.name))?;
+        if let Ok(mode_el) = el.first_child_by_attr(Some("mode"), "name", &mode.name) {
             fields.append(&mut field_map_from_bitfield_children(
-                el,
+                mode_el,
+                size,
                 values,
-                Some(mode_name),
+                Some(&mode.name),
             )?);
-            crate::Result::Ok(())
-        })?;
+        }
 
-    Ok(chip::Register {
-        name,
-        description,
-        mode,
-        address: util::parse_int(el.attr("offset")?)? + offset,
-        size: util::parse_int(el.attr("size")?)?,
-        access,
-        restriction: if fields.is_empty() {
-            chip::ValueRestriction::Any
+        let register_name = if modes.len() > 1 {
+            format!("{}_{}", name, mode.name)
         } else {
-            chip::ValueRestriction::Unsafe
-        },
-        fields,
-    })
+            name.clone()
+        };
+
+        registers.push(make_register(
+            register_name,
+            append_description(description.clone(), describe_mode(mode, &modes)),
+            registers.first().map(|r| r.name.clone()),
+            fields,
+        ));
+    }
+
+    Ok(registers)
 }
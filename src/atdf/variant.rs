@@ -0,0 +1,37 @@
+use crate::ElementExt;
+use crate::atdf;
+use crate::chip;
+use crate::util;
+
+fn parse_float(el: &xmltree::Element, name: &str) -> crate::Result<Option<f64>> {
+    match el.attributes.get(name) {
+        Some(v) if !v.is_empty() => v.parse().map(Some).map_err(|_| {
+            atdf::error::UnsupportedError::new(format!("value {v:?} of {name:?}"), el).into()
+        }),
+        _ => Ok(None),
+    }
+}
+
+pub fn parse(el: &xmltree::Element) -> crate::Result<chip::Variant> {
+    debug_assert!(el.name == "variant");
+
+    let non_empty = |name: &str| {
+        el.attributes
+            .get(name)
+            .and_then(|d| if !d.is_empty() { Some(d) } else { None })
+            .cloned()
+    };
+
+    Ok(chip::Variant {
+        order_code: el.attr("ordercode")?.clone(),
+        package: non_empty("package"),
+        pinout: non_empty("pinout"),
+        speed_max: non_empty("speedmax")
+            .map(|s| util::parse_int(&s))
+            .transpose()?,
+        vcc_min: parse_float(el, "vccmin")?,
+        vcc_max: parse_float(el, "vccmax")?,
+        temp_min: parse_float(el, "tempmin")?,
+        temp_max: parse_float(el, "tempmax")?,
+    })
+}
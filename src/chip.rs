@@ -11,21 +11,314 @@ pub struct Chip {
     pub vendor: Option<String>,
     pub version: Option<String>,
 
+    /// Device signature bytes, as read by programmers
+    pub signature: Option<Vec<u8>>,
+    pub properties: PropertyGroups,
+    pub variants: Vec<Variant>,
+    pub interfaces: Vec<Interface>,
+    pub parameters: Parameters,
+    pub pinouts: BTreeMap<String, Pinout>,
+
+    pub address_spaces: BTreeMap<String, AddressSpace>,
     pub peripherals: BTreeMap<String, Peripheral>,
     pub interrupts: BTreeMap<String, Interrupt>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// An address space of the chip, like program memory (`prog`) or the data space (`data`)
+#[derive(Debug, Clone)]
+pub struct AddressSpace {
+    pub name: String,
+    pub start: usize,
+    pub size: usize,
+    pub endianness: Option<Endianness>,
+
+    pub segments: Vec<MemorySegment>,
+}
+
+/// A region of memory inside an address space, e.g. `FLASH`, `EEPROM` or `INTERNAL_SRAM`
+#[derive(Debug, Clone)]
+pub struct MemorySegment {
+    pub name: String,
+    /// Kind of memory as given by the ATDF (`flash`, `ram`, `eeprom`, `io`, `fuses`, ...)
+    pub kind: String,
+    pub start: usize,
+    pub size: usize,
+    pub page_size: Option<usize>,
+    pub access: AccessMode,
+    pub executable: bool,
+    pub external: bool,
+}
+
+/// An orderable variant of the chip
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub order_code: String,
+    pub package: Option<String>,
+    pub pinout: Option<String>,
+    /// Maximum clock speed in Hz
+    pub speed_max: Option<usize>,
+    /// Supply voltage range in V
+    pub vcc_min: Option<f64>,
+    pub vcc_max: Option<f64>,
+    /// Temperature range in °C
+    pub temp_min: Option<f64>,
+    pub temp_max: Option<f64>,
+}
+
+/// Mapping of a package's physical pins to pads
+#[derive(Debug, Clone)]
+pub struct Pinout {
+    pub name: String,
+    pub description: Option<String>,
+    pub pins: Vec<Pin>,
+}
+
+impl Pinout {
+    /// Find the physical pin of a pad in this package
+    pub fn pin_for_pad(&self, pad: &str) -> Option<&Pin> {
+        self.pins.iter().find(|p| p.pad == pad)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pin {
+    /// Pin number or ball name (e.g. `1` or `A1`)
+    pub position: String,
+    /// Pad connected to this pin (e.g. `PA0` or `VCC`)
+    pub pad: String,
+}
+
+/// A peripheral function which can be routed to a pad
+#[derive(Debug, Clone)]
+pub struct Signal {
+    /// Signal group of the peripheral, e.g. `TXD` or `WO`
+    pub group: String,
+    pub index: Option<usize>,
+    /// Function of the pad, e.g. `default` or an alternate route like `TCA_ALT2`
+    pub function: String,
+    pub pad: String,
+    /// Field selecting this route, if any (e.g. `PORTMUX.TCAROUTEA.TCA0`)
+    pub field: Option<String>,
+}
+
+impl Signal {
+    /// Name of the signal, e.g. `TXD` or `WO0`
+    pub fn name(&self) -> String {
+        match self.index {
+            Some(i) => format!("{}{}", self.group, i),
+            None => self.group.clone(),
+        }
+    }
+}
+
+/// A programming or debugging interface of the chip
+#[derive(Debug, Clone)]
+pub struct Interface {
+    pub name: String,
+    /// Protocol of the interface, e.g. `updi`, `isp`, `pdi`, `dw` or `megajtag`
+    pub kind: String,
+}
+
+/// `<param>`s of a device or peripheral, mapping name to value
+pub type Parameters = BTreeMap<String, PropertyValue>;
+
+/// Property-groups of the ATDF, mapping group name to its properties
+pub type PropertyGroups = BTreeMap<String, BTreeMap<String, PropertyValue>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyValue {
+    Integer(usize),
+    String(String),
+}
+
+impl Chip {
+    /// All peripheral signals which can be routed to a pad
+    pub fn signals_on_pad<'a>(
+        &'a self,
+        pad: &'a str,
+    ) -> impl Iterator<Item = (&'a Peripheral, &'a Signal)> + 'a {
+        self.peripherals
+            .values()
+            .flat_map(|p| p.signals.iter().map(move |s| (p, s)))
+            .filter(move |(_, s)| s.pad == pad)
+    }
+
+    /// Look up a property from the ATDF property-groups
+    pub fn property(&self, group: &str, name: &str) -> Option<&PropertyValue> {
+        self.properties.get(group).and_then(|g| g.get(name))
+    }
+
+    /// Whether this is an ARM Cortex based device (like the SAM families)
+    pub fn is_cortex(&self) -> bool {
+        self.architecture.starts_with("CORTEX-")
+    }
+
+    /// The address space registers and RAM are mapped into
+    ///
+    /// This is the `data` space of AVRs, SAM devices only have a single `base` space.
+    pub fn main_address_space(&self) -> Option<&AddressSpace> {
+        self.address_spaces
+            .get("data")
+            .or_else(|| self.address_spaces.get("base"))
+    }
+
+    /// Only keep the peripherals, registers and memory of a certain address space
+    ///
+    /// This is useful to emit registers which are not part of the `data` space, like fuses, into
+    /// a separate SVD file or to drop them entirely.  Peripherals with registers in several
+    /// address spaces only keep those of the given one.  Interrupts are only kept for the main
+    /// address space.
+    pub fn retain_address_space(&mut self, address_space: &str) {
+        if self.main_address_space().map(|a| a.name.as_str()) != Some(address_space) {
+            self.interrupts.clear();
+        }
+
+        self.peripherals.retain(|_, p| {
+            if p.all_registers().is_empty() {
+                return p.address_space == address_space;
+            }
+            p.registers.retain(|_, r| r.address_space == address_space);
+            p.clusters
+                .retain(|_, c| c.retain_address_space(address_space));
+            p.address_space = address_space.to_string();
+            !p.all_registers().is_empty()
+        });
+        self.address_spaces.retain(|name, _| name == address_space);
+    }
+
+    /// Only keep the peripherals and memory of the main address space
+    ///
+    /// Registers outside of it (like fuses, lockbits or signatures) are not memory-mapped and
+    /// would otherwise show up at addresses of the main address space.
+    pub fn retain_main_address_space(&mut self) {
+        let Some(main) = self.main_address_space().map(|a| a.name.clone()) else {
+            return;
+        };
+        for p in self.peripherals.values() {
+            if p.all_registers().iter().any(|r| r.address_space != main) {
+                log::info!(
+                    "Leaving out registers of peripheral {} outside of the {} address space",
+                    p.name,
+                    main
+                );
+            }
+        }
+        self.retain_address_space(&main);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Peripheral {
     pub name: String,
     pub description: Option<String>,
+    /// Name of the ATDF module this peripheral is an instance of
+    pub module: String,
+    /// Name of the address space the registers of this peripheral are located in
+    pub address_space: String,
 
     pub registers: BTreeMap<String, Register>,
+    pub clusters: BTreeMap<String, Cluster>,
+    pub signals: Vec<Signal>,
+    pub parameters: Parameters,
 }
 
 impl Peripheral {
     pub fn base_address(&self) -> Option<usize> {
-        self.registers.values().map(|r| r.address).min()
+        self.registers
+            .values()
+            .map(|r| r.address)
+            .chain(self.clusters.values().map(|c| c.address))
+            .min()
+    }
+
+    /// All registers of this peripheral, including the ones inside of clusters
+    pub fn all_registers(&self) -> Vec<&Register> {
+        let mut registers: Vec<_> = self.registers.values().collect();
+        for cluster in self.clusters.values() {
+            registers.extend(cluster.all_registers());
+        }
+        registers
+    }
+
+    /// Address and size of every register, with each element of arrays listed
+    pub fn register_ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<_> = self.registers.values().flat_map(Register::ranges).collect();
+        for cluster in self.clusters.values() {
+            ranges.extend(cluster.register_ranges());
+        }
+        ranges
+    }
+}
+
+/// Repetition of a cluster, register or field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dim {
+    pub count: usize,
+    /// Distance between the start of two consecutive elements, in bytes for clusters and
+    /// registers and in bits for fields
+    pub increment: usize,
+}
+
+/// A group of registers inside a peripheral
+///
+/// Members of an ATDF union register-group are represented as clusters sharing the same address,
+/// where all but the first one name the first one as their `alternate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster {
+    pub name: String,
+    pub description: Option<String>,
+    pub address: usize,
+    pub alternate: Option<String>,
+    /// Set if the cluster is an array, `address` and the addresses of its registers are those of
+    /// the first element
+    pub dim: Option<Dim>,
+
+    pub registers: BTreeMap<String, Register>,
+    pub clusters: BTreeMap<String, Cluster>,
+}
+
+impl Cluster {
+    /// Drop all registers outside of `address_space`, returns whether any are left
+    fn retain_address_space(&mut self, address_space: &str) -> bool {
+        self.registers
+            .retain(|_, r| r.address_space == address_space);
+        self.clusters
+            .retain(|_, c| c.retain_address_space(address_space));
+        !self.registers.is_empty() || !self.clusters.is_empty()
+    }
+
+    /// All registers of this cluster, including the ones inside of nested clusters
+    pub fn all_registers(&self) -> Vec<&Register> {
+        let mut registers: Vec<_> = self.registers.values().collect();
+        for cluster in self.clusters.values() {
+            registers.extend(cluster.all_registers());
+        }
+        registers
+    }
+
+    /// Address and size of every register, with each element of (nested) arrays listed
+    pub fn register_ranges(&self) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<_> = self.registers.values().flat_map(Register::ranges).collect();
+        for cluster in self.clusters.values() {
+            ranges.extend(cluster.register_ranges());
+        }
+
+        let Some(dim) = self.dim else {
+            return ranges;
+        };
+        (0..dim.count)
+            .flat_map(|i| {
+                ranges
+                    .iter()
+                    .map(move |(address, size)| (address + i * dim.increment, *size))
+            })
+            .collect()
     }
 }
 
@@ -34,9 +327,11 @@ pub struct Interrupt {
     pub name: String,
     pub description: Option<String>,
     pub index: usize,
+    /// Name of the peripheral raising this interrupt, if known
+    pub module_instance: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccessMode {
     NoAccess,
     ReadOnly,
@@ -44,43 +339,97 @@ pub enum AccessMode {
     ReadWrite,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ValueRestriction {
     Unsafe,
     Any,
     Range(u64, u64),
-    Enumerated(BTreeMap<String, EnumeratedValue>),
+    Enumerated {
+        /// Name of the ATDF value-group the values are taken from
+        group: Option<String>,
+        values: BTreeMap<String, EnumeratedValue>,
+    },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Register {
     pub name: String,
     pub description: Option<String>,
     pub mode: Option<String>,
+    /// Name of the register this one is an alternative view of
+    pub alternate: Option<String>,
+    /// Name of the address space `address` is located in
+    pub address_space: String,
     pub address: usize,
     pub size: usize,
     pub access: AccessMode,
+    /// Access permitted to a debugger, if the ATDF restricts it
+    pub ocd_access: Option<AccessMode>,
     pub restriction: ValueRestriction,
+    /// Bits which are implemented in hardware, if the ATDF specifies them
+    pub mask: Option<u64>,
+    pub reset_value: u64,
+    pub reset_mask: u64,
+    /// Set if the register is an array, `address` is the one of the first element
+    pub dim: Option<Dim>,
 
     pub fields: BTreeMap<String, Field>,
 }
 
-#[derive(Debug, Clone)]
+impl Register {
+    /// Address and size of this register or of each of its elements
+    pub fn ranges(&self) -> Vec<(usize, usize)> {
+        let dim = self.dim.unwrap_or(Dim {
+            count: 1,
+            increment: self.size,
+        });
+        (0..dim.count)
+            .map(|i| (self.address + i * dim.increment, self.size))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Field {
     pub name: String,
     pub description: Option<String>,
     pub range: (usize, usize),
     pub access: AccessMode,
     pub restriction: ValueRestriction,
+    /// Set if this field is only one part of a bitfield whose bits are not contiguous
+    pub part: Option<FieldPart>,
+    /// Set if the field is an array, `range` is the one of the first element
+    pub dim: Option<Dim>,
+}
+
+/// Location of a field inside of a larger, non-contiguous bitfield
+///
+/// The value of the original bitfield is made up of all its parts, each `part.width()` bits
+/// wide and shifted to `offset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPart {
+    /// Name of the bitfield in the ATDF
+    pub bitfield: String,
+    /// Position of the lowest bit of this part in the bitfield's value
+    pub offset: usize,
 }
 
 impl Field {
     pub fn width(&self) -> usize {
         self.range.1 - self.range.0 + 1
     }
+
+    /// Bitmask of all bits spanned by this field, including all elements of an array
+    pub fn mask(&self) -> u64 {
+        let mask = (u64::MAX >> (63 - (self.range.1 - self.range.0))) << self.range.0;
+        match self.dim {
+            Some(dim) => (0..dim.count).fold(0, |m, i| m | mask << (i * dim.increment)),
+            None => mask,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnumeratedValue {
     pub name: String,
     pub description: Option<String>,
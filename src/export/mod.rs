@@ -0,0 +1,33 @@
+//! Additional machine-readable outputs besides the SVD file
+pub mod pinmux;
+pub mod variants;
+pub mod vectors;
+
+/// Quote a CSV field if necessary
+pub fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Format an optional value as a CSV field, leaving it empty if missing
+pub fn csv_optional<T: std::fmt::Display>(v: Option<T>) -> String {
+    v.map(|v| csv_field(&v.to_string())).unwrap_or_default()
+}
+
+/// Quote and escape a string for use in JSON
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,79 @@
+use crate::chip;
+use crate::export::csv_field;
+
+/// Row of the pin multiplexing table: a physical pin and all functions available on it
+fn rows<'a>(c: &'a chip::Chip, pinout: &'a chip::Pinout) -> Vec<(&'a chip::Pin, Vec<String>)> {
+    pinout
+        .pins
+        .iter()
+        .map(|pin| {
+            let mut functions: Vec<_> = c
+                .signals_on_pad(&pin.pad)
+                .map(|(peripheral, signal)| {
+                    // The function names the route (like `USART0_ALT`, selected through
+                    // PORTMUX) or the multiplexer setting (like the PMUX letter on SAM parts),
+                    // unless it merely repeats the peripheral
+                    if signal.function != "default"
+                        && signal.function != peripheral.module
+                        && signal.function != peripheral.name
+                    {
+                        format!(
+                            "{} {} ({})",
+                            peripheral.name,
+                            signal.name(),
+                            signal.function
+                        )
+                    } else {
+                        format!("{} {}", peripheral.name, signal.name())
+                    }
+                })
+                .collect();
+            functions.sort();
+            functions.dedup();
+            (pin, functions)
+        })
+        .collect()
+}
+
+/// Write the pin multiplexing table of one pinout as CSV
+pub fn generate_csv<W: std::io::Write>(
+    c: &chip::Chip,
+    pinout: &chip::Pinout,
+    mut w: W,
+) -> crate::Result<()> {
+    writeln!(w, "position,pad,functions")?;
+    for (pin, functions) in rows(c, pinout) {
+        writeln!(
+            w,
+            "{},{},{}",
+            csv_field(&pin.position),
+            csv_field(&pin.pad),
+            csv_field(&functions.join("; "))
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write the pin multiplexing table of one pinout as Markdown
+pub fn generate_markdown<W: std::io::Write>(
+    c: &chip::Chip,
+    pinout: &chip::Pinout,
+    mut w: W,
+) -> crate::Result<()> {
+    writeln!(w, "# {} {}", c.name, pinout.name)?;
+    writeln!(w)?;
+    writeln!(w, "| Pin | Pad | Functions |")?;
+    writeln!(w, "|-----|-----|-----------|")?;
+    for (pin, functions) in rows(c, pinout) {
+        writeln!(
+            w,
+            "| {} | {} | {} |",
+            pin.position,
+            pin.pad,
+            functions.join(", ").replace('|', "\\|")
+        )?;
+    }
+
+    Ok(())
+}
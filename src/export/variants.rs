@@ -0,0 +1,26 @@
+use crate::chip;
+use crate::export::{csv_field, csv_optional};
+
+/// Write a CSV table of all variants of the chip
+pub fn generate<W: std::io::Write>(c: &chip::Chip, mut w: W) -> crate::Result<()> {
+    writeln!(
+        w,
+        "ordercode,package,pinout,speedmax,vccmin,vccmax,tempmin,tempmax"
+    )?;
+    for v in c.variants.iter() {
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{}",
+            csv_field(&v.order_code),
+            csv_optional(v.package.as_ref()),
+            csv_optional(v.pinout.as_ref()),
+            csv_optional(v.speed_max),
+            csv_optional(v.vcc_min),
+            csv_optional(v.vcc_max),
+            csv_optional(v.temp_min),
+            csv_optional(v.temp_max),
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::DisplayError;
+use crate::chip;
+use crate::export::json_string;
+
+/// An entry of the interrupt vector table
+#[derive(Debug)]
+pub struct Vector<'a> {
+    pub interrupt: &'a chip::Interrupt,
+    /// Byte address of the entry in program memory
+    pub address: usize,
+}
+
+/// Layout of the interrupt vector table as implied by the architecture and program memory size
+#[derive(Debug)]
+pub struct VectorTable {
+    pub start: usize,
+    /// Size of one entry in bytes
+    pub vector_size: usize,
+    /// Number of entries preceding the one with index 0 (the Cortex-M exceptions)
+    pub index_offset: usize,
+}
+
+impl VectorTable {
+    pub fn new(c: &chip::Chip) -> crate::Result<Self> {
+        // SAM devices have no separate program space, their flash is part of the `base` space
+        let (start, size) = match c.address_spaces.get("prog") {
+            Some(prog) => (prog.start, prog.size),
+            None => c
+                .main_address_space()
+                .and_then(|a| a.segments.iter().find(|s| s.kind == "flash"))
+                .map(|s| (s.start, s.size))
+                .ok_or(NoProgramMemory)?,
+        };
+
+        if c.is_cortex() {
+            return Ok(VectorTable {
+                start,
+                vector_size: 4,
+                index_offset: 16,
+            });
+        }
+
+        // AVRs with more than 8 KiB of flash use 4-byte `JMP` instructions as vectors, smaller ones
+        // 2-byte `RJMP`s.
+        Ok(VectorTable {
+            start,
+            vector_size: if size > 0x2000 { 4 } else { 2 },
+            index_offset: 0,
+        })
+    }
+
+    /// All interrupts of the chip, sorted by index
+    pub fn vectors<'a>(&self, c: &'a chip::Chip) -> Vec<Vector<'a>> {
+        let mut interrupts: Vec<_> = c.interrupts.values().collect();
+        interrupts.sort_by_key(|i| i.index);
+
+        interrupts
+            .into_iter()
+            .map(|interrupt| Vector {
+                interrupt,
+                address: self.start + (self.index_offset + interrupt.index) * self.vector_size,
+            })
+            .collect()
+    }
+}
+
+/// Write a `device.x`-style linker script fragment defaulting all handlers to `DefaultHandler`
+///
+/// The reset vector of AVRs (index 0) is left out as it is provided by the runtime.
+pub fn generate_linker_script<W: std::io::Write>(c: &chip::Chip, mut w: W) -> crate::Result<()> {
+    let table = VectorTable::new(c)?;
+    for vector in table.vectors(c) {
+        if table.index_offset == 0 && vector.interrupt.index == 0 {
+            continue;
+        }
+        writeln!(w, "PROVIDE({} = DefaultHandler);", vector.interrupt.name)?;
+    }
+
+    Ok(())
+}
+
+/// Write a JSON manifest of the interrupt vector table
+pub fn generate_manifest<W: std::io::Write>(c: &chip::Chip, mut w: W) -> crate::Result<()> {
+    let table = VectorTable::new(c)?;
+    let vectors = table.vectors(c);
+
+    writeln!(w, "{{")?;
+    writeln!(w, "  \"device\": {},", json_string(&c.name))?;
+    writeln!(w, "  \"vector_size\": {},", table.vector_size)?;
+    writeln!(w, "  \"vectors\": [")?;
+    for (i, vector) in vectors.iter().enumerate() {
+        writeln!(
+            w,
+            "    {{ \"index\": {}, \"name\": {}, \"address\": {} }}{}",
+            vector.interrupt.index,
+            json_string(&vector.interrupt.name),
+            vector.address,
+            if i + 1 < vectors.len() { "," } else { "" }
+        )?;
+    }
+    writeln!(w, "  ]")?;
+    writeln!(w, "}}")?;
+
+    Ok(())
+}
+
+struct NoProgramMemory;
+
+impl DisplayError for NoProgramMemory {
+    fn format(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        write!(
+            w,
+            "No `prog` address space found, cannot locate the interrupt vector table!"
+        )
+    }
+}
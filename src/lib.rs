@@ -10,6 +10,7 @@ pub mod cli;
 pub mod atdf;
 pub mod chip;
 pub mod elementext;
+pub mod export;
 pub mod svd;
 pub mod util;
 
@@ -28,14 +29,62 @@ pub struct Atdf2SvdOptions {
     #[options(free)]
     atdf_path: Option<std::path::PathBuf>,
 
-    /// [optional] Path where to save the SVD file
+    /// [optional] Path where to save the SVD file (a directory with --all-devices)
     #[options(free)]
     svd_path: Option<std::path::PathBuf>,
 
+    /// Name of the device to convert if the file contains more than one.
+    #[options(no_short, long = "device", meta = "NAME")]
+    device: Option<String>,
+
+    /// Convert all devices of the file, each into its own SVD file.
+    #[options(no_short, long = "all-devices")]
+    all_devices: bool,
+
     /// List of patches to apply.
     #[options(long = "auto-patches")]
     auto_patches: Vec<String>,
 
+    /// Write a CSV table of the device variants to this path.
+    #[options(no_short, long = "variants", meta = "PATH")]
+    variants: Option<std::path::PathBuf>,
+
+    /// Write one pin multiplexing table per pinout into this directory.
+    #[options(no_short, long = "pinmux", meta = "DIR")]
+    pinmux: Option<std::path::PathBuf>,
+
+    /// Format of the pin multiplexing tables (`csv` or `markdown`).
+    #[options(no_short, long = "pinmux-format", meta = "FORMAT")]
+    pinmux_format: Option<String>,
+
+    /// Write a linker script fragment providing default interrupt handlers to this path.
+    #[options(no_short, long = "linker-script", meta = "PATH")]
+    linker_script: Option<std::path::PathBuf>,
+
+    /// Write a JSON manifest of the interrupt vector table to this path.
+    #[options(no_short, long = "vector-manifest", meta = "PATH")]
+    vector_manifest: Option<std::path::PathBuf>,
+
+    /// Override the device description in the SVD.
+    #[options(no_short, long = "description", meta = "TEXT")]
+    description: Option<String>,
+
+    /// Override the vendor in the SVD.
+    #[options(no_short, long = "vendor", meta = "NAME")]
+    vendor: Option<String>,
+
+    /// Override the device version in the SVD (defaults to the pack or ATDF schema version).
+    #[options(no_short, long = "svd-version", meta = "VERSION")]
+    svd_version: Option<String>,
+
+    /// How to handle interrupts sharing an index (`concatenate`, `first`, `aliases` or `fail`).
+    #[options(no_short, long = "interrupt-collisions", meta = "POLICY")]
+    interrupt_collisions: Option<atdf::CollisionPolicy>,
+
+    /// Only emit peripherals of this address space (`fuses`, ...) instead of the data space.
+    #[options(no_short, long = "address-space", meta = "NAME")]
+    address_space: Option<String>,
+
     #[options(short = "d", long = "debug")]
     debug: bool,
 
@@ -66,27 +115,137 @@ pub fn run(args: Atdf2SvdOptions) {
 
     cli::setup(args.verbose);
 
-    let atdf_file = if let Some(atdf_path) = args.atdf_path {
+    let pack_version = args.atdf_path.as_deref().and_then(atdf::pack::version);
+    let atdf_file = if let Some(ref atdf_path) = args.atdf_path {
         std::fs::File::open(atdf_path).unwrap_or_else(|e| cli::exit_with_error(e.into()))
     } else {
         log::error!("Missing atdf-file argument");
         std::process::exit(1);
     };
 
-    let svd_file: Box<dyn std::io::Write> = if let Some(p) = args.svd_path {
-        Box::new(std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into())))
-    } else {
-        Box::new(std::io::stdout())
-    };
-
     let patches = HashSet::from_iter(args.auto_patches.iter().cloned());
-    let chip = atdf::parse(atdf_file, &patches).unwrap_or_else(|e| cli::exit_with_error(e));
+    let interrupt_collisions = args.interrupt_collisions.unwrap_or_default();
+    let mut chips =
+        atdf::parse_all_with_collision_policy(atdf_file, &patches, interrupt_collisions)
+            .unwrap_or_else(|e| cli::exit_with_error(e));
 
-    if args.debug {
-        eprintln!("{:#?}", chip);
+    if let Some(device) = args.device {
+        chips.retain(|c| c.name == device);
+        if chips.is_empty() {
+            log::error!("Device {device:?} not found in atdf-file");
+            std::process::exit(1);
+        }
+    } else if chips.len() > 1 && !args.all_devices {
+        let names: Vec<_> = chips.iter().map(|c| c.name.as_str()).collect();
+        log::error!(
+            "The atdf-file contains multiple devices ({}), select one using --device or use --all-devices",
+            names.join(", ")
+        );
+        std::process::exit(1);
     }
 
-    svd::generate(&chip, svd_file).unwrap_or_else(|e| cli::exit_with_error(e));
+    for mut chip in chips {
+        if let Some(version) = args.svd_version.as_ref().or(pack_version.as_ref()) {
+            chip.version = Some(version.clone());
+        }
+        if let Some(ref vendor) = args.vendor {
+            chip.vendor = Some(vendor.clone());
+        }
+        if let Some(ref description) = args.description {
+            chip.description = Some(description.clone());
+        }
+
+        // The vector table is located using the program memory, write it before dropping other
+        // address spaces.
+        if let Some(ref p) = args.linker_script {
+            let p = output_path(p, &chip, "x", args.all_devices);
+            let f = std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into()));
+            export::vectors::generate_linker_script(&chip, f)
+                .unwrap_or_else(|e| cli::exit_with_error(e));
+        }
+
+        if let Some(ref p) = args.vector_manifest {
+            let p = output_path(p, &chip, "vectors.json", args.all_devices);
+            let f = std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into()));
+            export::vectors::generate_manifest(&chip, f)
+                .unwrap_or_else(|e| cli::exit_with_error(e));
+        }
+
+        // The vendor extensions describe the vector table as well
+        let vendor_extensions = svd::cpu::generate_vendor_extensions(&chip);
+
+        match args.address_space {
+            Some(ref address_space) => chip.retain_address_space(address_space),
+            None => chip.retain_main_address_space(),
+        }
+
+        if args.debug {
+            eprintln!("{:#?}", chip);
+        }
+
+        if let Some(ref p) = args.variants {
+            let p = output_path(p, &chip, "variants.csv", args.all_devices);
+            let f = std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into()));
+            export::variants::generate(&chip, f).unwrap_or_else(|e| cli::exit_with_error(e));
+        }
+
+        if let Some(ref dir) = args.pinmux {
+            write_pinmux_tables(&chip, dir, args.pinmux_format.as_deref());
+        }
+
+        let svd_file: Box<dyn std::io::Write> = match args.svd_path {
+            Some(ref dir) if args.all_devices => {
+                let p = dir.join(format!("{}.svd", chip.name));
+                Box::new(
+                    std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into())),
+                )
+            }
+            Some(ref p) => Box::new(
+                std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into())),
+            ),
+            None if args.all_devices => {
+                log::error!("Missing svd-path argument, required with --all-devices");
+                std::process::exit(1);
+            }
+            None => Box::new(std::io::stdout()),
+        };
+
+        svd::generate_with_extensions(&chip, vendor_extensions.as_deref(), svd_file)
+            .unwrap_or_else(|e| cli::exit_with_error(e));
+    }
+}
+
+#[cfg(feature = "cli")]
+fn write_pinmux_tables(chip: &chip::Chip, dir: &std::path::Path, format: Option<&str>) {
+    let (extension, generate): (_, fn(_, _, _) -> _) = match format {
+        None | Some("csv") => ("csv", export::pinmux::generate_csv::<std::fs::File>),
+        Some("markdown") => ("md", export::pinmux::generate_markdown::<std::fs::File>),
+        Some(f) => {
+            log::error!("Unknown pinmux format {f:?}, use `csv` or `markdown`");
+            std::process::exit(1);
+        }
+    };
+
+    for pinout in chip.pinouts.values() {
+        let p = dir.join(format!("{}_{}.{}", chip.name, pinout.name, extension));
+        let f = std::fs::File::create(p).unwrap_or_else(|e| cli::exit_with_error(e.into()));
+        generate(chip, pinout, f).unwrap_or_else(|e| cli::exit_with_error(e));
+    }
+}
+
+/// With `--all-devices`, output paths are directories which receive one file per device
+#[cfg(feature = "cli")]
+fn output_path(
+    path: &std::path::Path,
+    chip: &chip::Chip,
+    extension: &str,
+    all_devices: bool,
+) -> std::path::PathBuf {
+    if all_devices {
+        path.join(format!("{}.{}", chip.name, extension))
+    } else {
+        path.to_path_buf()
+    }
 }
 
 pub fn run_test(atdf: &mut dyn std::io::Read, auto_patches: Vec<&str>) -> String {
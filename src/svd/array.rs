@@ -0,0 +1,222 @@
+use crate::chip;
+use crate::util;
+use std::collections::BTreeMap;
+
+/// A series of registers like `PIN0CTRL` to `PIN7CTRL` which can be emitted as one SVD array
+#[derive(Debug)]
+pub struct RegisterArray<'a> {
+    /// Name with `%s` in place of the index
+    pub name: String,
+    pub description: Option<String>,
+    pub indices: Vec<usize>,
+    pub increment: usize,
+    pub registers: Vec<&'a chip::Register>,
+}
+
+/// Registers are only combined if everything but their name, description and address matches
+fn same_layout(a: &chip::Register, b: &chip::Register) -> bool {
+    a.mode == b.mode
+        && a.address_space == b.address_space
+        && a.size == b.size
+        && a.access == b.access
+        && a.ocd_access == b.ocd_access
+        && a.restriction == b.restriction
+        && a.mask == b.mask
+        && a.reset_value == b.reset_value
+        && a.reset_mask == b.reset_mask
+        && a.fields == b.fields
+}
+
+/// Replace the whole-word occurrence of `index` in a description by `placeholder`
+fn description_template(description: &str, index: usize, placeholder: &str) -> String {
+    let index = index.to_string();
+    description
+        .split(' ')
+        .map(|word| if word == index { placeholder } else { word })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether `register` with `index` can continue the series in `run`
+fn continues(run: &[(usize, &chip::Register)], index: usize, register: &chip::Register) -> bool {
+    let (Some((first_index, first)), Some((last_index, last))) = (run.first(), run.last()) else {
+        return true;
+    };
+
+    let stride_matches = match run.get(1) {
+        Some((_, second)) => register.address == last.address + (second.address - first.address),
+        None => register.address >= last.address + last.size,
+    };
+    let template = |r: &chip::Register, i| {
+        r.description
+            .as_ref()
+            .map(|d| description_template(d, i, "%s"))
+    };
+
+    *last_index + 1 == index
+        && stride_matches
+        && same_layout(first, register)
+        && template(first, *first_index) == template(register, index)
+}
+
+/// Find the array at the start of a candidate series
+///
+/// The members are split into runs of consecutive indices at a constant stride.  Only the first
+/// run can become an array: later ones would either share its name or be indexed from zero while
+/// the register with the lowest index stays a plain register (e.g. `gpior(0)` being `GPIOR1`).
+fn find_run<'a>(
+    name: String,
+    mut members: Vec<(usize, &'a chip::Register)>,
+) -> Option<RegisterArray<'a>> {
+    members.sort_by_key(|(i, _)| *i);
+
+    let mut runs: Vec<Vec<(usize, &chip::Register)>> = vec![vec![]];
+    for (index, register) in members {
+        let run = runs.last_mut().unwrap();
+        if !continues(run, index, register) {
+            // A pair can also be the start of a series with a tighter stride, e.g. GPIOR0 being
+            // far away from GPIOR1 and GPIOR2.
+            let regroup = match run.as_slice() {
+                [(_, first), (_, second)] => {
+                    continues(&run[1..], index, register)
+                        && register.address - second.address < second.address - first.address
+                }
+                _ => false,
+            };
+            let next = if regroup { run.pop() } else { None };
+            runs.push(next.into_iter().collect());
+        }
+        runs.last_mut().unwrap().push((index, register));
+    }
+
+    let run = runs.into_iter().next().filter(|run| run.len() > 1)?;
+    let (first_index, first) = run[0];
+    Some(RegisterArray {
+        name,
+        description: first
+            .description
+            .as_ref()
+            .map(|d| description_template(d, first_index, "n")),
+        indices: run.iter().map(|(i, _)| *i).collect(),
+        increment: run[1].1.address - first.address,
+        registers: run.iter().map(|(_, r)| *r).collect(),
+    })
+}
+
+/// Find series of numbered registers with identical layout at a constant stride
+///
+/// Each register ends up in at most one array, longer series are preferred.  Registers which
+/// have or are alternates are never combined.
+pub fn find_register_arrays<'a>(
+    registers: impl IntoIterator<Item = &'a chip::Register>,
+) -> Vec<RegisterArray<'a>> {
+    let registers: Vec<_> = registers.into_iter().collect();
+
+    let mut candidates: BTreeMap<String, Vec<(usize, &chip::Register)>> = BTreeMap::new();
+    for register in registers.iter() {
+        let is_alternate = register.alternate.is_some()
+            || registers
+                .iter()
+                .any(|r| r.alternate.as_ref() == Some(&register.name));
+        // Registers which already are arrays are kept as they are
+        if is_alternate || register.dim.is_some() {
+            continue;
+        }
+
+        for (prefix, index, suffix) in util::split_numbers(&register.name) {
+            candidates
+                .entry(format!("{prefix}%s{suffix}"))
+                .or_default()
+                .push((index, register));
+        }
+    }
+
+    let mut arrays: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|(name, members)| find_run(name, members))
+        .collect();
+    arrays.sort_by_key(|a| std::cmp::Reverse(a.registers.len()));
+
+    let mut used: Vec<&str> = vec![];
+    arrays.retain(|a| {
+        if a.registers.iter().any(|r| used.contains(&r.name.as_str())) {
+            false
+        } else {
+            used.extend(a.registers.iter().map(|r| r.name.as_str()));
+            true
+        }
+    });
+    arrays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register(name: &str, address: usize) -> chip::Register {
+        chip::Register {
+            name: name.to_string(),
+            description: None,
+            mode: None,
+            alternate: None,
+            address_space: "data".to_string(),
+            address,
+            size: 1,
+            access: chip::AccessMode::ReadWrite,
+            ocd_access: None,
+            restriction: chip::ValueRestriction::Any,
+            mask: None,
+            reset_value: 0,
+            reset_mask: 0xff,
+            dim: None,
+            fields: BTreeMap::new(),
+        }
+    }
+
+    fn arrays(registers: &[chip::Register]) -> Vec<(String, Vec<usize>, usize)> {
+        find_register_arrays(registers)
+            .into_iter()
+            .map(|a| (a.name, a.indices, a.increment))
+            .collect()
+    }
+
+    #[test]
+    fn consecutive_series() {
+        let registers: Vec<_> = (0..4)
+            .map(|i| register(&format!("PIN{i}CTRL"), 0x10 + i))
+            .collect();
+        assert_eq!(
+            arrays(&registers),
+            [("PIN%sCTRL".to_string(), vec![0, 1, 2, 3], 1)]
+        );
+    }
+
+    #[test]
+    fn constant_stride() {
+        let registers = [
+            register("CH0", 0x20),
+            register("CH1", 0x24),
+            register("CH2", 0x28),
+            register("CH3", 0x30),
+        ];
+        assert_eq!(arrays(&registers), [("CH%s".to_string(), vec![0, 1, 2], 4)]);
+    }
+
+    #[test]
+    fn series_starting_above_zero() {
+        let registers = [register("OCR1", 0x10), register("OCR2", 0x11)];
+        assert_eq!(arrays(&registers), [("OCR%s".to_string(), vec![1, 2], 1)]);
+    }
+
+    #[test]
+    fn regroup_keeps_first_register_plain() {
+        // GPIOR0 is far away from GPIOR1 and GPIOR2, neither a pair with its stride nor an array
+        // of the other two without it are emitted.
+        let registers = [
+            register("GPIOR0", 0x3e),
+            register("GPIOR1", 0x4a),
+            register("GPIOR2", 0x4b),
+        ];
+        assert!(arrays(&registers).is_empty());
+    }
+}
@@ -1,80 +1,97 @@
 use crate::chip;
 
 pub fn generate(c: &chip::Chip) -> crate::Result<svd_rs::Device> {
+    // Registers of AVRs are mostly 8 bits wide, those of Cortex-M based SAM devices 32 bits
+    let (width, reset_mask) = if c.is_cortex() {
+        (32, 0xffff_ffff)
+    } else {
+        (8, 0xff)
+    };
+
     let device = svd_rs::Device::builder()
         .xmlns_xs("http://www.w3.org/2001/XMLSchema-instance".to_string())
         .schema_version("1.1".to_string())
         .no_namespace_schema_location("CMSIS-SVD.xsd".to_string());
 
     let device = device
-        .vendor(Some("Atmel".to_owned()))
-        .name(c.name.clone())
-        .version("1.0".to_string())
-        .description(
-            c.description
+        .vendor(
+            c.vendor
                 .clone()
-                .unwrap_or_else(|| "No description available.".to_string()),
+                .or_else(|| Some(default_vendor(c).to_owned())),
         )
+        .name(c.name.clone())
+        .version(c.version.clone().unwrap_or_else(|| "1.0".to_string()))
+        .description(generate_description(c))
         .address_unit_bits(8)
-        .width(8)
+        .width(width)
         .default_register_properties(
             svd_rs::RegisterProperties::new()
-                .size(Some(8))
+                .size(Some(width))
                 .access(Some(svd_rs::Access::ReadWrite))
                 .reset_value(Some(0))
-                .reset_mask(Some(0xff)),
+                .reset_mask(Some(reset_mask)),
         );
 
-    let device = device.cpu(Some(generate_cpu(c)?));
+    let device = device.cpu(Some(crate::svd::cpu::generate(c)?));
 
-    let mut peripherals = c
-        .peripherals
-        .values()
-        .filter(has_registers)
-        .map(crate::svd::peripheral::generate)
-        .collect::<Result<Vec<_>, _>>()?;
-
-    if crate::svd::interrupt::generate(&mut peripherals, c).is_err() {
-        log::warn!("Could not generate CPU interrupts");
+    // Instances of a module with identical registers are derived from the first one of them
+    let mut peripherals = vec![];
+    let mut generated: Vec<&chip::Peripheral> = vec![];
+    let mut emitted = crate::svd::restriction::EmittedValueGroups::default();
+    for p in c.peripherals.values().filter(has_registers) {
+        match generated
+            .iter()
+            .find(|g| crate::svd::peripheral::same_layout(g, p))
+        {
+            Some(base) => peripherals.push(crate::svd::peripheral::generate_derived(p, base)?),
+            None => {
+                peripherals.push(crate::svd::peripheral::generate(p, &mut emitted)?);
+                generated.push(p);
+            }
+        }
     }
 
+    crate::svd::interrupt::generate(&mut peripherals, c)?;
+
+    peripherals.extend(crate::svd::memory::generate(c)?);
+
     device
         .peripherals(peripherals)
         .build(svd_rs::ValidateLevel::Strict)
         .map_err(crate::Error::from)
 }
 
+fn generate_description(c: &chip::Chip) -> String {
+    let variants: Vec<_> = c
+        .variants
+        .iter()
+        .map(|v| match v.package {
+            Some(ref package) => format!("{} ({})", v.order_code, package),
+            None => v.order_code.clone(),
+        })
+        .collect();
+
+    match (&c.description, variants.is_empty()) {
+        (Some(d), true) => d.clone(),
+        (Some(d), false) => format!("{} Variants: {}.", d, variants.join(", ")),
+        (None, true) => "No description available.".to_string(),
+        (None, false) => format!("Variants: {}.", variants.join(", ")),
+    }
+}
+
 fn has_registers(peripheral: &&chip::Peripheral) -> bool {
-    let regs = !peripheral.registers.is_empty();
+    let regs = !peripheral.registers.is_empty() || !peripheral.clusters.is_empty();
     if !regs {
         log::warn!("No registers found for peripheral {}", peripheral.name);
     }
     regs
 }
 
-fn generate_cpu(c: &chip::Chip) -> crate::Result<svd_rs::Cpu> {
-    let cpu_name = architecture_to_name(&c.architecture);
-
-    svd_rs::Cpu::builder()
-        .name(cpu_name)
-        .revision("r0p0".to_string())
-        .endian(svd_rs::Endian::Little)
-        .mpu_present(false)
-        .fpu_present(false)
-        .nvic_priority_bits(4)
-        .has_vendor_systick(false)
-        .build(svd_rs::ValidateLevel::Strict)
-        .map_err(crate::Error::from)
-}
-
-fn architecture_to_name(architecture: &str) -> String {
-    // Convert CORTEX-.* to C.* format. For example:
-    //
-    // - CORTEX-A5 -> CA5
-    // - CORTEX-M0PLUS -> CM0PLUS
-    let cortex_name = architecture
-        .strip_prefix("CORTEX-")
-        .map(|suffix| format!("C{suffix}"));
-
-    cortex_name.unwrap_or("other".to_string())
+/// SAM devices were released after the acquisition by Microchip, AVRs keep their original vendor
+fn default_vendor(c: &chip::Chip) -> &'static str {
+    if c.is_cortex() {
+        "Microchip Technology"
+    } else {
+        "Atmel"
+    }
 }
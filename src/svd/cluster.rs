@@ -0,0 +1,48 @@
+use crate::chip;
+use crate::svd;
+use std::convert::TryInto;
+
+/// Generate a cluster inside of the peripheral or cluster at `scope`
+pub fn generate(
+    c: &chip::Cluster,
+    base: u32,
+    scope: &str,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<svd_rs::Cluster> {
+    let address: u32 = c.address.try_into()?;
+    // Arrays are indexed like `GROUP[0]`, `GROUP[1]`, ...
+    let name = match c.dim {
+        Some(_) => format!("{}[%s]", c.name),
+        None => c.name.clone(),
+    };
+    let path = format!("{scope}.{name}");
+
+    let mut children = svd::register::generate_list(c.registers.values(), address, &path, emitted)?;
+    for cluster in c.clusters.values() {
+        children.push(
+            generate(cluster, address, &path, emitted).map(svd_rs::RegisterCluster::Cluster)?,
+        );
+    }
+
+    let info = svd_rs::ClusterInfo::builder()
+        .name(name)
+        .description(c.description.clone().or_else(|| {
+            log::warn!("Description missing for cluster {:?}", c.name);
+            Some("No Description.".to_owned())
+        }))
+        .alternate_cluster(c.alternate.clone())
+        .address_offset(address - base)
+        .children(children)
+        .build(svd_rs::ValidateLevel::Strict)?;
+
+    match c.dim {
+        Some(dim) => {
+            let dim = svd_rs::DimElement::builder()
+                .dim(dim.count.try_into()?)
+                .dim_increment(dim.increment.try_into()?)
+                .build(svd_rs::ValidateLevel::Strict)?;
+            Ok(svd_rs::Cluster::Array(info, dim))
+        }
+        None => Ok(svd_rs::Cluster::Single(info)),
+    }
+}
@@ -0,0 +1,163 @@
+use crate::chip;
+use crate::export::vectors::VectorTable;
+
+pub fn generate(c: &chip::Chip) -> crate::Result<svd_rs::Cpu> {
+    let cpu_name = architecture_to_name(&c.architecture);
+
+    // AVRs have no NVIC, CMSIS requires the element nonetheless.  SAM devices describe their core
+    // through the CMSIS defines in the device parameters.
+    let cpu = if c.is_cortex() {
+        svd_rs::Cpu::builder()
+            .revision(generate_revision(c))
+            .mpu_present(flag(c, "__MPU_PRESENT"))
+            .fpu_present(flag(c, "__FPU_PRESENT"))
+            .vtor_present(
+                c.parameters
+                    .contains_key("__VTOR_PRESENT")
+                    .then(|| flag(c, "__VTOR_PRESENT")),
+            )
+            .nvic_priority_bits(
+                integer_parameter(c, "__NVIC_PRIO_BITS")
+                    .unwrap_or(4)
+                    .try_into()?,
+            )
+            .has_vendor_systick(flag(c, "__Vendor_SysTickConfig"))
+    } else {
+        svd_rs::Cpu::builder()
+            .revision("r0p0".to_string())
+            .mpu_present(false)
+            .fpu_present(false)
+            .nvic_priority_bits(0)
+            .has_vendor_systick(false)
+    };
+
+    cpu.name(cpu_name)
+        .endian(generate_endian(c))
+        .build(svd_rs::ValidateLevel::Strict)
+        .map_err(crate::Error::from)
+}
+
+fn integer_parameter(c: &chip::Chip, name: &str) -> Option<usize> {
+    match c.parameters.get(name)? {
+        chip::PropertyValue::Integer(v) => Some(*v),
+        chip::PropertyValue::String(_) => None,
+    }
+}
+
+fn flag(c: &chip::Chip, name: &str) -> bool {
+    integer_parameter(c, name).is_some_and(|v| v != 0)
+}
+
+/// Core revision in the `rNpM` format from the `__CM*_REV` parameter
+///
+/// The parameter encodes the revision as `0xNNMM`, e.g. `0x0001` for r0p1.
+fn generate_revision(c: &chip::Chip) -> String {
+    let revision = c
+        .parameters
+        .iter()
+        .find(|(name, _)| name.starts_with("__CM") && name.ends_with("_REV"))
+        .and_then(|(name, _)| integer_parameter(c, name))
+        .unwrap_or(0);
+    format!("r{}p{}", revision >> 8, revision & 0xff)
+}
+
+fn architecture_to_name(architecture: &str) -> String {
+    // Convert CORTEX-.* to C.* format. For example:
+    //
+    // - CORTEX-A5 -> CA5
+    // - CORTEX-M0PLUS -> CM0PLUS
+    let cortex_name = architecture
+        .strip_prefix("CORTEX-")
+        .map(|suffix| format!("C{suffix}"));
+
+    cortex_name.unwrap_or("other".to_string())
+}
+
+/// Endianness of the main address space, falling back to any other address space
+fn generate_endian(c: &chip::Chip) -> svd_rs::Endian {
+    let endianness = c
+        .main_address_space()
+        .and_then(|a| a.endianness)
+        .or_else(|| c.address_spaces.values().find_map(|a| a.endianness));
+
+    match endianness {
+        Some(chip::Endianness::Big) => svd_rs::Endian::Big,
+        Some(chip::Endianness::Little) | None => svd_rs::Endian::Little,
+    }
+}
+
+/// Instruction set variant of an AVR core
+///
+/// The `CORE_VERSION` parameter of the `CPU` module names the core revision, which maps onto
+/// the instruction set variants of the AVR instruction set manual.
+fn avr_core(c: &chip::Chip) -> Option<String> {
+    if c.architecture == "AVR8L" {
+        return Some("AVRrc".to_string());
+    }
+
+    let core_version = match c.peripherals.get("CPU")?.parameters.get("CORE_VERSION")? {
+        chip::PropertyValue::String(v) => v.as_str(),
+        chip::PropertyValue::Integer(_) => return None,
+    };
+    Some(
+        match core_version {
+            "V0" => "AVR",
+            "V0E" | "V1" | "V2" => "AVRe",
+            "V2E" | "V3" => "AVRe+",
+            "V3X" => "AVRxm",
+            "V4" => "AVRxt",
+            v => v,
+        }
+        .to_string(),
+    )
+}
+
+/// Whether any peripheral has a register of the given name
+fn has_register(c: &chip::Chip, name: &str) -> bool {
+    c.peripherals
+        .values()
+        .any(|p| p.all_registers().iter().any(|r| r.name == name))
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Facts about AVR cores which have no place in the SVD `<cpu>` element
+///
+/// Returns the `<vendorExtensions>` element or `None` for non-AVR architectures.
+pub fn generate_vendor_extensions(c: &chip::Chip) -> Option<String> {
+    if c.is_cortex() {
+        return None;
+    }
+
+    let core = avr_core(c);
+    let mut facts = vec![("architecture", c.architecture.clone())];
+    if let Some(core) = core.clone() {
+        facts.push(("core", core));
+    }
+    let multiplier = match core.as_deref() {
+        Some("AVR" | "AVRe" | "AVRrc") => Some(false),
+        Some("AVRe+" | "AVRxm" | "AVRxt") => Some(true),
+        _ => None,
+    };
+    if let Some(multiplier) = multiplier {
+        facts.push(("hardwareMultiplier", multiplier.to_string()));
+    }
+    facts.push(("rampz", has_register(c, "RAMPZ").to_string()));
+    facts.push(("eind", has_register(c, "EIND").to_string()));
+    if let Ok(table) = VectorTable::new(c) {
+        facts.push(("vectorSize", table.vector_size.to_string()));
+    }
+
+    let mut xml = String::from("  <vendorExtensions>\n    <avr>\n");
+    for (name, value) in facts {
+        xml.push_str(&format!("      <{name}>{}</{name}>\n", escape(&value)));
+    }
+    xml.push_str("    </avr>\n  </vendorExtensions>\n");
+    Some(xml)
+}
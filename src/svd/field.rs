@@ -2,16 +2,30 @@ use crate::chip;
 use crate::svd;
 use std::convert::TryInto;
 
-pub fn generate(f: &chip::Field) -> crate::Result<svd_rs::Field> {
-    let (write_constraint, enumerated_values) =
-        svd::restriction::generate(&f.restriction, f.width().try_into()?)?;
+/// Generate a field of the register at `path`
+pub fn generate(
+    f: &chip::Field,
+    path: &str,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<svd_rs::Field> {
+    // Field arrays are named like `NAME0`, `NAME1`, ...
+    let name = match f.dim {
+        Some(_) => format!("{}%s", f.name),
+        None => f.name.clone(),
+    };
+    let (write_constraint, enumerated_values) = svd::restriction::generate(
+        &f.restriction,
+        f.width().try_into()?,
+        &format!("{path}.{name}"),
+        emitted,
+    )?;
     let (lsb, msb) = (
         u32::try_from(f.range.0).unwrap(),
         u32::try_from(f.range.1).unwrap(),
     );
 
-    let field = svd_rs::FieldInfo::builder()
-        .name(f.name.clone())
+    let info = svd_rs::FieldInfo::builder()
+        .name(name)
         .description(f.description.clone().or_else(|| {
             log::warn!("Description missing for field {:?}", f.name);
             Some("No Description.".to_owned())
@@ -24,8 +38,17 @@ pub fn generate(f: &chip::Field) -> crate::Result<svd_rs::Field> {
         .access(svd::restriction::generate_access(f.access))
         .write_constraint(write_constraint)
         .enumerated_values(enumerated_values)
-        .build(svd_rs::ValidateLevel::Strict)
-        .map(svd_rs::Field::Single)?;
+        .build(svd_rs::ValidateLevel::Strict)?;
 
-    Ok(field)
+    match f.dim {
+        Some(dim) => {
+            let dim = svd_rs::DimElement::builder()
+                .dim(dim.count.try_into()?)
+                .dim_increment(dim.increment.try_into()?)
+                .dim_index(Some((0..dim.count).map(|i| i.to_string()).collect()))
+                .build(svd_rs::ValidateLevel::Strict)?;
+            Ok(svd_rs::Field::Array(info, dim))
+        }
+        None => Ok(svd_rs::Field::Single(info)),
+    }
 }
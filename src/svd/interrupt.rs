@@ -1,44 +1,50 @@
-use crate::DisplayError;
 use crate::chip;
 use std::convert::TryInto;
 
+/// Add each interrupt to the peripheral raising it
+///
+/// Interrupts without an owner, or whose owner is not part of the SVD, are added to the (first)
+/// peripheral named `CPU` instead.  Devices without one (like SAM devices) drop them with a
+/// warning rather than attributing them to an unrelated peripheral.
 pub fn generate(peripherals: &mut [svd_rs::Peripheral], c: &chip::Chip) -> crate::Result<()> {
-    // Find the (first) peripheral with the name `CPU` to then add the interrupts to it.
-    for peripheral in peripherals.iter_mut() {
-        if peripheral.name == "CPU" {
-            let mut interrupts = c.interrupts.values().collect::<Vec<_>>();
-            interrupts.sort_by_key(|a| a.index);
+    let mut interrupts = c.interrupts.values().collect::<Vec<_>>();
+    interrupts.sort_by_key(|a| a.index);
 
-            let interrupts = interrupts
-                .into_iter()
-                .map(|interrupt| {
-                    svd_rs::Interrupt::builder()
-                        .name(interrupt.name.clone())
-                        .description(interrupt.description.clone().or_else(|| {
-                            log::warn!("Description missing for interrupt {:?}", interrupt.name);
-                            Some("No Description.".to_owned())
-                        }))
-                        .value(interrupt.index.try_into().map_err(crate::Error::from)?)
-                        .build(svd_rs::ValidateLevel::Strict)
-                        .map_err(crate::Error::from)
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+    let mut unowned = vec![];
+    for interrupt in interrupts {
+        let svd_interrupt = generate_interrupt(interrupt)?;
+        let owner = interrupt
+            .module_instance
+            .as_ref()
+            .and_then(|m| peripherals.iter_mut().find(|p| &p.name == m));
+        match owner {
+            Some(peripheral) => peripheral.interrupt.push(svd_interrupt),
+            None => unowned.push(svd_interrupt),
+        }
+    }
 
-            peripheral.interrupt.extend(interrupts);
-            return Ok(());
+    match peripherals.iter_mut().find(|p| p.name == "CPU") {
+        Some(cpu) => cpu.interrupt.extend(unowned),
+        None => {
+            for interrupt in unowned {
+                log::warn!(
+                    "No peripheral found for interrupt {:?}, dropping it",
+                    interrupt.name
+                );
+            }
         }
     }
-    // No peripheral named `CPU` found.
-    Err(NoCPUPeripheral.into())
+    Ok(())
 }
 
-struct NoCPUPeripheral;
-
-impl DisplayError for NoCPUPeripheral {
-    fn format(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
-        write!(
-            w,
-            "No `CPU` peripheral found, to which interrupts can be added!"
-        )
-    }
+fn generate_interrupt(interrupt: &chip::Interrupt) -> crate::Result<svd_rs::Interrupt> {
+    svd_rs::Interrupt::builder()
+        .name(interrupt.name.clone())
+        .description(interrupt.description.clone().or_else(|| {
+            log::warn!("Description missing for interrupt {:?}", interrupt.name);
+            Some("No Description.".to_owned())
+        }))
+        .value(interrupt.index.try_into().map_err(crate::Error::from)?)
+        .build(svd_rs::ValidateLevel::Strict)
+        .map_err(crate::Error::from)
 }
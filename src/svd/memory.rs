@@ -0,0 +1,101 @@
+use crate::chip;
+use std::convert::TryInto;
+
+/// Memory segments which consist of registers and are thus already described by peripherals
+const REGISTER_SEGMENT_KINDS: [&str; 2] = ["io", "regs"];
+
+/// Generate pseudo-peripherals for the non-register memory in the main address space
+///
+/// Each memory segment (like SRAM or memory-mapped EEPROM) becomes a peripheral without registers
+/// and a single `buffer` address block.  Segments overlapping any of the register peripherals
+/// (e.g. memory-mapped fuses) or sharing a name with one are skipped as they are already
+/// described by those.
+pub fn generate(c: &chip::Chip) -> crate::Result<Vec<svd_rs::Peripheral>> {
+    let Some(data) = c.main_address_space() else {
+        return Ok(vec![]);
+    };
+
+    let register_ranges: Vec<_> = c
+        .peripherals
+        .values()
+        .filter(|p| p.address_space == data.name)
+        .flat_map(|p| p.register_ranges())
+        .map(|(address, size)| (address, address + size))
+        .collect();
+
+    data.segments
+        .iter()
+        .filter(|s| !REGISTER_SEGMENT_KINDS.contains(&s.kind.as_str()))
+        .filter(|s| !c.peripherals.contains_key(&s.name))
+        .filter(|s| {
+            let end = s.start + s.size;
+            let overlaps = register_ranges
+                .iter()
+                .any(|(r_start, r_end)| *r_start < end && s.start < *r_end);
+            if overlaps {
+                log::debug!(
+                    "Memory segment {} overlaps with registers, skipping",
+                    s.name
+                );
+            }
+            !overlaps
+        })
+        .map(generate_segment)
+        .collect()
+}
+
+fn generate_segment(s: &chip::MemorySegment) -> crate::Result<svd_rs::Peripheral> {
+    let address_block = svd_rs::AddressBlock::builder()
+        .offset(0)
+        .size(s.size.try_into()?)
+        .usage(svd_rs::AddressBlockUsage::Buffer)
+        .build(svd_rs::ValidateLevel::Strict)?;
+
+    svd_rs::PeripheralInfo::builder()
+        .name(s.name.clone())
+        .description(Some(describe_segment(s)))
+        .base_address(s.start.try_into()?)
+        .default_register_properties(
+            svd_rs::RegisterProperties::new()
+                .access(crate::svd::restriction::generate_access(s.access)),
+        )
+        .address_block(Some(vec![address_block]))
+        .build(svd_rs::ValidateLevel::Strict)
+        .map(svd_rs::Peripheral::Single)
+        .map_err(crate::Error::from)
+}
+
+/// Description of a memory segment including its access and page size
+fn describe_segment(s: &chip::MemorySegment) -> String {
+    let kind = match (s.kind.as_str(), s.name.as_str()) {
+        (_, "MAPPED_PROGMEM") => "Program memory mapped into the data space".to_string(),
+        ("flash", _) => "Flash memory".to_string(),
+        ("ram", _) => "SRAM".to_string(),
+        ("eeprom", _) => "EEPROM".to_string(),
+        ("fuses", _) => "Fuses".to_string(),
+        ("lockbits", _) => "Lock bits".to_string(),
+        ("signatures", _) => "Signature bytes".to_string(),
+        ("user_signatures", _) => "User signature row".to_string(),
+        ("user_page", _) => "User page".to_string(),
+        ("osccal", _) => "Oscillator calibration bytes".to_string(),
+        (_, name) => format!("{name} memory segment"),
+    };
+
+    let mut properties = vec![
+        match s.access {
+            chip::AccessMode::ReadWrite => "read-write",
+            chip::AccessMode::ReadOnly => "read-only",
+            chip::AccessMode::WriteOnly => "write-only",
+            chip::AccessMode::NoAccess => "no access",
+        }
+        .to_string(),
+    ];
+    if s.executable {
+        properties.push("executable".to_string());
+    }
+    if let Some(page_size) = s.page_size {
+        properties.push(format!("{page_size} byte pages"));
+    }
+
+    format!("{} ({})", kind, properties.join(", "))
+}
@@ -1,13 +1,36 @@
+pub mod array;
 pub mod chip;
+pub mod cluster;
+pub mod cpu;
 pub mod field;
 pub mod interrupt;
+pub mod memory;
 pub mod peripheral;
 pub mod register;
 pub mod restriction;
 
-pub fn generate<W: std::io::Write>(c: &crate::chip::Chip, mut w: W) -> crate::Result<()> {
+pub fn generate<W: std::io::Write>(c: &crate::chip::Chip, w: W) -> crate::Result<()> {
+    generate_with_extensions(c, cpu::generate_vendor_extensions(c).as_deref(), w)
+}
+
+/// Generate the SVD with `<vendorExtensions>` which were generated beforehand
+///
+/// The extensions describe the interrupt vector table, which is located using the program
+/// memory.  Generate them before dropping that using `retain_address_space()`.
+pub fn generate_with_extensions<W: std::io::Write>(
+    c: &crate::chip::Chip,
+    extensions: Option<&str>,
+    mut w: W,
+) -> crate::Result<()> {
     let device = chip::generate(c)?;
-    let svd_xml = svd_encoder::encode(&device)?;
+    let mut svd_xml = svd_encoder::encode(&device)?;
+
+    // svd-rs has no support for vendor extensions, they go last into the <device> element
+    if let Some(extensions) = extensions {
+        if let Some(end) = svd_xml.rfind("</device>") {
+            svd_xml.insert_str(end, extensions);
+        }
+    }
     w.write_all(svd_xml.as_bytes())?;
 
     Ok(())
@@ -1,10 +1,11 @@
 use crate::chip;
 use crate::svd;
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 
 fn create_address_blocks(p: &chip::Peripheral) -> crate::Result<Option<Vec<svd_rs::AddressBlock>>> {
-    let mut registers: Vec<_> = p.registers.values().collect();
-    registers.sort_by_key(|a| a.address);
+    let mut registers = p.register_ranges();
+    registers.sort_unstable();
 
     let base = p.base_address().expect("no base address");
     let new_address_block = |offset: usize, size| {
@@ -19,17 +20,20 @@ fn create_address_blocks(p: &chip::Peripheral) -> crate::Result<Option<Vec<svd_r
     };
 
     let mut address_blocks = Vec::new();
-    let mut current_offset = registers[0].address;
+    let mut current_offset = registers[0].0;
     let mut current_size = 0;
-    for reg in registers.into_iter() {
+    for (address, size) in registers.into_iter() {
         let current_address = current_offset + current_size;
-        if current_address == reg.address {
-            current_size += reg.size;
+        if current_address == address {
+            current_size += size;
+        } else if current_address > address {
+            // Overlapping registers (e.g. from union clusters) extend the current block at most
+            current_size = current_size.max(address + size - current_offset);
         } else {
             address_blocks.push(new_address_block(current_offset, current_size.try_into()?)?);
 
-            current_offset = reg.address;
-            current_size = reg.size;
+            current_offset = address;
+            current_size = size;
         }
     }
     address_blocks.push(new_address_block(current_offset, current_size.try_into()?)?);
@@ -43,17 +47,22 @@ fn create_address_blocks(p: &chip::Peripheral) -> crate::Result<Option<Vec<svd_r
     Ok(address_blocks)
 }
 
-pub fn generate(p: &chip::Peripheral) -> crate::Result<svd_rs::Peripheral> {
+pub fn generate(
+    p: &chip::Peripheral,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<svd_rs::Peripheral> {
     let base: u32 = p
         .base_address()
         .expect("Could not retrieve peripheral base address")
         .try_into()?;
 
-    let registers = p
-        .registers
-        .values()
-        .map(|r| svd::register::generate(r, base).map(svd_rs::RegisterCluster::Register))
-        .collect::<Result<Vec<_>, _>>()?;
+    let mut registers = svd::register::generate_list(p.registers.values(), base, &p.name, emitted)?;
+    for cluster in p.clusters.values() {
+        registers.push(
+            svd::cluster::generate(cluster, base, &p.name, emitted)
+                .map(svd_rs::RegisterCluster::Cluster)?,
+        );
+    }
 
     svd_rs::PeripheralInfo::builder()
         .name(p.name.clone())
@@ -68,3 +77,69 @@ pub fn generate(p: &chip::Peripheral) -> crate::Result<svd_rs::Peripheral> {
         .map(svd_rs::Peripheral::Single)
         .map_err(crate::Error::from)
 }
+
+/// Generate a peripheral which only differs from `base` in its name and address
+pub fn generate_derived(
+    p: &chip::Peripheral,
+    base: &chip::Peripheral,
+) -> crate::Result<svd_rs::Peripheral> {
+    let address: u32 = p
+        .base_address()
+        .expect("Could not retrieve peripheral base address")
+        .try_into()?;
+
+    svd_rs::PeripheralInfo::builder()
+        .name(p.name.clone())
+        .description(p.description.clone())
+        .base_address(u64::from(address))
+        .derived_from(Some(base.name.clone()))
+        .build(svd_rs::ValidateLevel::Strict)
+        .map(svd_rs::Peripheral::Single)
+        .map_err(crate::Error::from)
+}
+
+fn relocate_registers(
+    registers: &BTreeMap<String, chip::Register>,
+    base: usize,
+) -> BTreeMap<String, chip::Register> {
+    registers
+        .iter()
+        .map(|(name, r)| {
+            let r = chip::Register {
+                address: r.address - base,
+                ..r.clone()
+            };
+            (name.clone(), r)
+        })
+        .collect()
+}
+
+fn relocate_clusters(
+    clusters: &BTreeMap<String, chip::Cluster>,
+    base: usize,
+) -> BTreeMap<String, chip::Cluster> {
+    clusters
+        .iter()
+        .map(|(name, c)| {
+            let c = chip::Cluster {
+                address: c.address - base,
+                registers: relocate_registers(&c.registers, base),
+                clusters: relocate_clusters(&c.clusters, base),
+                ..c.clone()
+            };
+            (name.clone(), c)
+        })
+        .collect()
+}
+
+/// Whether two instances of the same module have identical registers relative to their base
+pub fn same_layout(a: &chip::Peripheral, b: &chip::Peripheral) -> bool {
+    let (Some(base_a), Some(base_b)) = (a.base_address(), b.base_address()) else {
+        return false;
+    };
+
+    a.module == b.module
+        && a.address_space == b.address_space
+        && relocate_registers(&a.registers, base_a) == relocate_registers(&b.registers, base_b)
+        && relocate_clusters(&a.clusters, base_a) == relocate_clusters(&b.clusters, base_b)
+}
@@ -1,9 +1,105 @@
 use crate::chip;
+use crate::svd;
 use crate::svd::restriction::generate_access;
 
-pub fn generate(r: &chip::Register, base: u32) -> crate::Result<svd_rs::Register> {
-    let (write_constraint, _) =
-        crate::svd::restriction::generate(&r.restriction, u32::try_from(r.size).unwrap() * 8)?;
+/// Generate all registers of a peripheral or cluster, combining numbered series into arrays
+///
+/// `scope` is the path of the peripheral or cluster, e.g. `PERIPHERAL.CLUSTER`.
+pub fn generate_list<'a>(
+    registers: impl IntoIterator<Item = &'a chip::Register>,
+    base: u32,
+    scope: &str,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<Vec<svd_rs::RegisterCluster>> {
+    let registers: Vec<_> = registers.into_iter().collect();
+    let arrays = svd::array::find_register_arrays(registers.iter().copied());
+
+    let mut generated = vec![];
+    for r in registers {
+        match arrays
+            .iter()
+            .find(|a| a.registers.iter().any(|m| m.name == r.name))
+        {
+            Some(a) if a.registers[0].name == r.name => {
+                generated.push(generate_array(a, base, scope, emitted)?)
+            }
+            Some(_) => (),
+            None => generated.push(generate(r, base, scope, emitted)?),
+        }
+    }
+    Ok(generated
+        .into_iter()
+        .map(svd_rs::RegisterCluster::Register)
+        .collect())
+}
+
+pub fn generate(
+    r: &chip::Register,
+    base: u32,
+    scope: &str,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<svd_rs::Register> {
+    let Some(dim) = r.dim else {
+        return generate_info(r, base, &format!("{scope}.{}", r.name), emitted)?
+            .build(svd_rs::ValidateLevel::Strict)
+            .map(svd_rs::Register::Single)
+            .map_err(crate::Error::from);
+    };
+
+    let name = format!("{}[%s]", r.name);
+    let info = generate_info(r, base, &format!("{scope}.{name}"), emitted)?
+        .name(name)
+        .build(svd_rs::ValidateLevel::Strict)?;
+
+    let dim = svd_rs::DimElement::builder()
+        .dim(dim.count.try_into()?)
+        .dim_increment(dim.increment.try_into()?)
+        .build(svd_rs::ValidateLevel::Strict)?;
+
+    Ok(svd_rs::Register::Array(info, dim))
+}
+
+fn generate_array(
+    a: &svd::array::RegisterArray,
+    base: u32,
+    scope: &str,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<svd_rs::Register> {
+    let info = generate_info(
+        a.registers[0],
+        base,
+        &format!("{scope}.{}", a.name),
+        emitted,
+    )?
+    .name(a.name.clone())
+    .description(
+        a.description
+            .clone()
+            .or_else(|| Some("No Description.".to_owned())),
+    )
+    .build(svd_rs::ValidateLevel::Strict)?;
+
+    let dim = svd_rs::DimElement::builder()
+        .dim(u32::try_from(a.indices.len())?)
+        .dim_increment(u32::try_from(a.increment)?)
+        .dim_index(Some(a.indices.iter().map(usize::to_string).collect()))
+        .build(svd_rs::ValidateLevel::Strict)?;
+
+    Ok(svd_rs::Register::Array(info, dim))
+}
+
+fn generate_info(
+    r: &chip::Register,
+    base: u32,
+    path: &str,
+    emitted: &mut svd::restriction::EmittedValueGroups,
+) -> crate::Result<svd_rs::RegisterInfoBuilder> {
+    let (write_constraint, _) = svd::restriction::generate(
+        &r.restriction,
+        u32::try_from(r.size).unwrap() * 8,
+        path,
+        emitted,
+    )?;
 
     let register = svd_rs::RegisterInfo::builder()
         .name(r.name.clone())
@@ -18,24 +114,35 @@ pub fn generate(r: &chip::Register, base: u32) -> crate::Result<svd_rs::Register
             None
         })
         .access(generate_access(r.access))
+        .reset_value(Some(r.reset_value))
+        .reset_mask(Some(r.reset_mask))
         .write_constraint(write_constraint)
-        .alternate_group(r.mode.clone());
+        .read_action(generate_read_action(r))
+        .alternate_group(r.mode.clone())
+        .alternate_register(r.alternate.clone());
 
     let mut fields = r.fields.values().collect::<Vec<_>>();
     fields.sort_by_key(|a| a.range.0);
 
     let fields = fields
         .into_iter()
-        .map(crate::svd::field::generate)
+        .map(|f| svd::field::generate(f, path, emitted))
         .collect::<Result<Vec<_>, _>>()?;
 
-    register
-        .fields(if !fields.is_empty() {
-            Some(fields)
-        } else {
-            None
-        })
-        .build(svd_rs::ValidateLevel::Strict)
-        .map(svd_rs::Register::Single)
-        .map_err(crate::Error::from)
+    Ok(register.fields(if !fields.is_empty() {
+        Some(fields)
+    } else {
+        None
+    }))
+}
+
+fn generate_read_action(r: &chip::Register) -> Option<svd_rs::ReadAction> {
+    // `ocd-rw` restricts what the on-chip debugger may do with a register.  Registers it may
+    // only read (`ocd-rw="R"`, like `PINB`) are safe to display, but those it must not access at
+    // all (`ocd-rw=""`, like `UDR0`) may change state when read, so debuggers should not read
+    // them behind the user's back.
+    match r.ocd_access {
+        Some(chip::AccessMode::NoAccess) => Some(svd_rs::ReadAction::Modify),
+        _ => None,
+    }
 }
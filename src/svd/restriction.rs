@@ -1,9 +1,29 @@
 use crate::chip;
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 
+/// Value-groups which already have an `<enumeratedValues>` in the SVD
+///
+/// Later uses of the same group with the same values are derived from the first one.
+#[derive(Debug, Default)]
+pub struct EmittedValueGroups(Vec<EmittedValueGroup>);
+
+#[derive(Debug)]
+struct EmittedValueGroup {
+    group: String,
+    values: BTreeMap<String, chip::EnumeratedValue>,
+    /// Path of the `<enumeratedValues>` like `PERIPHERAL.REGISTER.FIELD.GROUP`
+    path: String,
+}
+
+/// Generate write constraint and enumerated values of a restriction
+///
+/// `path` is the path of the field the restriction belongs to.
 pub fn generate(
     restriction: &chip::ValueRestriction,
     width: u32,
+    path: &str,
+    emitted: &mut EmittedValueGroups,
 ) -> crate::Result<(
     Option<svd_rs::WriteConstraint>,
     Vec<svd_rs::EnumeratedValues>,
@@ -33,30 +53,64 @@ pub fn generate(
             )),
             vec![],
         ),
-        chip::ValueRestriction::Enumerated(v) => {
-            let mut values = v.values().collect::<Vec<_>>();
-            values.sort_by_key(|a| a.value);
-
-            let values = values
-                .into_iter()
-                .map(generate_enumerated)
-                .collect::<Result<Vec<_>, _>>()?;
-
-            let enumerated_values = svd_rs::EnumeratedValues::builder()
-                .values(values)
-                .build(svd_rs::ValidateLevel::Strict)?;
-
-            (
-                Some(svd_rs::WriteConstraint::UseEnumeratedValues(true)),
-                vec![enumerated_values],
-            )
-        }
+        chip::ValueRestriction::Enumerated { group, values } => (
+            Some(svd_rs::WriteConstraint::UseEnumeratedValues(true)),
+            vec![generate_enumerated_values(group, values, path, emitted)?],
+        ),
         _ => (None, vec![]),
     };
 
     Ok(restrictions)
 }
 
+fn generate_enumerated_values(
+    group: &Option<String>,
+    values: &BTreeMap<String, chip::EnumeratedValue>,
+    path: &str,
+    emitted: &mut EmittedValueGroups,
+) -> crate::Result<svd_rs::EnumeratedValues> {
+    let Some(group) = group else {
+        return generate_values(values)?
+            .build(svd_rs::ValidateLevel::Strict)
+            .map_err(crate::Error::from);
+    };
+
+    if let Some(e) = emitted
+        .0
+        .iter()
+        .find(|e| &e.group == group && &e.values == values)
+    {
+        return svd_rs::EnumeratedValues::builder()
+            .derived_from(Some(e.path.clone()))
+            .build(svd_rs::ValidateLevel::Strict)
+            .map_err(crate::Error::from);
+    }
+
+    emitted.0.push(EmittedValueGroup {
+        group: group.clone(),
+        values: values.clone(),
+        path: format!("{path}.{group}"),
+    });
+    generate_values(values)?
+        .name(Some(group.clone()))
+        .build(svd_rs::ValidateLevel::Strict)
+        .map_err(crate::Error::from)
+}
+
+fn generate_values(
+    values: &BTreeMap<String, chip::EnumeratedValue>,
+) -> crate::Result<svd_rs::EnumeratedValuesBuilder> {
+    let mut values = values.values().collect::<Vec<_>>();
+    values.sort_by_key(|a| a.value);
+
+    let values = values
+        .into_iter()
+        .map(generate_enumerated)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(svd_rs::EnumeratedValues::builder().values(values))
+}
+
 pub fn generate_enumerated(e: &chip::EnumeratedValue) -> crate::Result<svd_rs::EnumeratedValue> {
     svd_rs::EnumeratedValue::builder()
         .name(e.name.clone())
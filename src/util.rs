@@ -11,21 +11,57 @@ pub fn parse_int(s: &str) -> crate::Result<usize> {
     .map_err(Into::into)
 }
 
-/// Parse a bitmask and return the range and whether the full range is covered
-pub fn parse_mask(s: &str) -> crate::Result<Option<((usize, usize), bool)>> {
+/// Parse a bitmask into the ranges of contiguous bits it covers, starting with the lowest one
+pub fn parse_mask(s: &str) -> crate::Result<Vec<(usize, usize)>> {
     let mask = parse_int(s)?;
-    // An empty mask cannot have a range
-    if mask == 0 {
-        return Ok(None);
+
+    let mut runs: Vec<(usize, usize)> = vec![];
+    for bit in (0..mem::size_of::<usize>() * 8).filter(|i| (mask & (1 << *i)) > 0) {
+        match runs.last_mut() {
+            Some(run) if run.1 + 1 == bit => run.1 = bit,
+            _ => runs.push((bit, bit)),
+        }
     }
 
-    let bits_set = (0..mem::size_of::<usize>() * 8).filter(|i| (mask & (1 << *i)) > 0);
-    let range = (bits_set.clone().min().unwrap(), bits_set.max().unwrap());
+    Ok(runs)
+}
 
-    let range_bitmask = ((1 << (range.1 - range.0 + 1)) - 1) << range.0;
-    let has_intermediate = range_bitmask ^ mask != 0;
+/// Return a bitmask covering all bits of a register with the given size in bytes
+pub fn size_to_mask(size: usize) -> u64 {
+    let bits = size.saturating_mul(8);
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
 
-    Ok(Some((range, has_intermediate)))
+/// Split a name at each of its decimal numbers into prefix, number and suffix
+///
+/// `"CH0CTRL1"` yields `("CH", 0, "CTRL1")` and `("CH0CTRL", 1, "")`.  Numbers with leading
+/// zeros are skipped as they cannot be reproduced from the index alone.
+pub fn split_numbers(name: &str) -> Vec<(&str, usize, &str)> {
+    let mut splits = vec![];
+    let mut start = None;
+    for (i, c) in name
+        .char_indices()
+        .chain(std::iter::once((name.len(), ' ')))
+    {
+        match (start, c.is_ascii_digit()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let digits = &name[s..i];
+                if let Ok(n) = digits.parse::<usize>() {
+                    if n.to_string() == digits {
+                        splits.push((&name[..s], n, &name[i..]));
+                    }
+                }
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    splits
 }
 
 #[cfg(test)]
@@ -40,4 +76,30 @@ mod tests {
         assert!(parse_int("ff").is_err());
         assert!(parse_int("-7").is_err());
     }
+
+    #[test]
+    fn parse_bitmask() {
+        assert_eq!(parse_mask("0x0").ok(), Some(vec![]));
+        assert_eq!(parse_mask("0x30").ok(), Some(vec![(4, 5)]));
+        assert_eq!(parse_mask("0x27").ok(), Some(vec![(0, 2), (5, 5)]));
+    }
+
+    #[test]
+    fn size_mask() {
+        assert_eq!(size_to_mask(0), 0);
+        assert_eq!(size_to_mask(1), 0xff);
+        assert_eq!(size_to_mask(2), 0xffff);
+        assert_eq!(size_to_mask(8), u64::MAX);
+    }
+
+    #[test]
+    fn split_name_numbers() {
+        assert_eq!(split_numbers("PIN3CTRL"), vec![("PIN", 3, "CTRL")]);
+        assert_eq!(
+            split_numbers("CH10CTRL1"),
+            vec![("CH", 10, "CTRL1"), ("CH10CTRL", 1, "")]
+        );
+        assert_eq!(split_numbers("GPIOR07"), vec![]);
+        assert_eq!(split_numbers("CTRLA"), vec![]);
+    }
 }
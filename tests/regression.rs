@@ -33,6 +33,13 @@ fn atmega4809() {
     insta::assert_snapshot!(svd);
 }
 
+#[test]
+fn atsamd21g18a() {
+    let mut atdf = std::fs::File::open("tests/atsamd21g18a.atdf").unwrap();
+    let svd = atdf2svd::run_test(&mut atdf, vec![]);
+    insta::assert_snapshot!(svd);
+}
+
 #[test]
 fn atmega328p_unsafe_nofuse() {
     let mut atdf = std::fs::File::open("tests/atmega328p.atdf").unwrap();
@@ -42,3 +49,133 @@ fn atmega328p_unsafe_nofuse() {
     );
     insta::assert_snapshot!(svd);
 }
+
+#[test]
+fn attiny817_vectors() {
+    let atdf = std::fs::File::open("tests/attiny817.atdf").unwrap();
+    let chip = atdf2svd::atdf::parse(atdf, &Default::default()).unwrap_or_else(|e| e.to_panic());
+    let mut output = Vec::new();
+    atdf2svd::export::vectors::generate_linker_script(&chip, &mut output)
+        .unwrap_or_else(|e| e.to_panic());
+    atdf2svd::export::vectors::generate_manifest(&chip, &mut output)
+        .unwrap_or_else(|e| e.to_panic());
+    insta::assert_snapshot!(String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn attiny817_pinmux() {
+    let atdf = std::fs::File::open("tests/attiny817.atdf").unwrap();
+    let chip = atdf2svd::atdf::parse(atdf, &Default::default()).unwrap_or_else(|e| e.to_panic());
+    let pinout = &chip.pinouts["QFN24"];
+    let mut output = Vec::new();
+    atdf2svd::export::pinmux::generate_csv(&chip, pinout, &mut output)
+        .unwrap_or_else(|e| e.to_panic());
+    atdf2svd::export::pinmux::generate_markdown(&chip, pinout, &mut output)
+        .unwrap_or_else(|e| e.to_panic());
+    insta::assert_snapshot!(String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn atmega4809_signals() {
+    let atdf = std::fs::File::open("tests/atmega4809.atdf").unwrap();
+    let chip = atdf2svd::atdf::parse(atdf, &Default::default()).unwrap_or_else(|e| e.to_panic());
+    let qfn = &chip.pinouts["QFN48"];
+
+    // USART0 TXD is routed to PA0, or to PA4 through PORTMUX
+    for (pad, function, position) in [("PA0", "USART0", "44"), ("PA4", "USART0_ALT", "48")] {
+        let (_, signal) = chip
+            .signals_on_pad(pad)
+            .find(|(p, s)| p.name == "USART0" && s.name() == "TXD")
+            .unwrap_or_else(|| panic!("USART0 TXD missing on {pad}"));
+        assert_eq!(signal.function, function);
+        assert_eq!(signal.field.as_deref(), Some("PORTMUX.USARTROUTEA.USART0"));
+        assert_eq!(qfn.pin_for_pad(pad).unwrap().position, position);
+    }
+
+    assert!(chip.signals_on_pad("VDD").next().is_none());
+    assert!(qfn.pin_for_pad("PZ9").is_none());
+}
+
+#[test]
+fn atmega328p_main_address_space() {
+    let atdf = std::fs::File::open("tests/atmega328p.atdf").unwrap();
+    let mut chip =
+        atdf2svd::atdf::parse(atdf, &Default::default()).unwrap_or_else(|e| e.to_panic());
+    chip.retain_main_address_space();
+    assert!(chip.peripherals.contains_key("PORTB"));
+    assert!(!chip.peripherals.contains_key("FUSE"));
+    assert!(!chip.peripherals.contains_key("LOCKBIT"));
+}
+
+#[test]
+fn atmega328p_fuses() {
+    let atdf = std::fs::File::open("tests/atmega328p.atdf").unwrap();
+    let mut chip =
+        atdf2svd::atdf::parse(atdf, &Default::default()).unwrap_or_else(|e| e.to_panic());
+    chip.retain_address_space("fuses");
+    let mut output = Vec::new();
+    atdf2svd::svd::generate(&chip, &mut output).unwrap_or_else(|e| e.to_panic());
+    insta::assert_snapshot!(String::from_utf8(output).unwrap());
+}
+
+#[test]
+fn multiple_devices() {
+    let atdf = std::fs::File::open("tests/multiple_devices.atdf").unwrap();
+    let chips =
+        atdf2svd::atdf::parse_all(atdf, &Default::default()).unwrap_or_else(|e| e.to_panic());
+    let names: Vec<_> = chips.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["ATtiny25", "ATtiny45"]);
+    assert_eq!(chips[0].address_spaces["prog"].size, 0x800);
+    assert_eq!(chips[1].address_spaces["prog"].size, 0x1000);
+
+    // Variants and pinouts are shared by the file, each device only keeps its own
+    let order_codes = |c: &atdf2svd::chip::Chip| {
+        c.variants
+            .iter()
+            .map(|v| v.order_code.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(order_codes(&chips[0]), ["ATtiny25-20PU"]);
+    assert_eq!(order_codes(&chips[1]), ["ATtiny45-20PU", "ATtiny45-20SU"]);
+    let pinouts = |c: &atdf2svd::chip::Chip| c.pinouts.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(pinouts(&chips[0]), ["PDIP8"]);
+    assert_eq!(pinouts(&chips[1]), ["PDIP8", "SOIC8"]);
+
+    // A single device can not be picked without knowing which one
+    let atdf = std::fs::File::open("tests/multiple_devices.atdf").unwrap();
+    assert!(atdf2svd::atdf::parse(atdf, &Default::default()).is_err());
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn multiple_devices_cli() {
+    let run = |args: &[&std::ffi::OsStr]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_atdf2svd"))
+            .arg("tests/multiple_devices.atdf")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    assert!(!run(&[]).status.success());
+
+    let output = run(&["--device".as_ref(), "ATtiny45".as_ref()]);
+    assert!(output.status.success());
+    let svd = String::from_utf8(output.stdout).unwrap();
+    assert!(svd.contains("<name>ATtiny45</name>"));
+    assert!(!svd.contains("<name>ATtiny25</name>"));
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("multiple_devices");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = run(&[
+        dir.as_ref(),
+        "--all-devices".as_ref(),
+        "--linker-script".as_ref(),
+        dir.as_ref(),
+    ]);
+    assert!(output.status.success());
+    for name in ["ATtiny25.svd", "ATtiny25.x", "ATtiny45.svd", "ATtiny45.x"] {
+        assert!(dir.join(name).is_file(), "{name} missing");
+    }
+}